@@ -1,4 +1,3 @@
-mod flow;
 mod shared;
 mod subs;
 
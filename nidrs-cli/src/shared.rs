@@ -9,23 +9,19 @@ pub fn exec_cmd(prefix: &str, cmd: &mut std::process::Command) -> Result<(), any
 
     if let Some(stderr) = child.stderr.take() {
         let reader = std::io::BufReader::new(stderr);
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                line.split("\r").for_each(|line| {
-                    eprintln!("[{}] {}", prefix, line);
-                });
-            }
+        for line in reader.lines().map_while(Result::ok) {
+            line.split('\r').for_each(|line| {
+                eprintln!("[{}] {}", prefix, line);
+            });
         }
     }
 
     if let Some(stdout) = child.stdout.take() {
         let reader = std::io::BufReader::new(stdout);
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                line.split("\r").for_each(|line| {
-                    println!("[{}] {}", prefix, line);
-                });
-            }
+        for line in reader.lines().map_while(Result::ok) {
+            line.split('\r').for_each(|line| {
+                println!("[{}] {}", prefix, line);
+            });
         }
     }
 
@@ -4,6 +4,7 @@ mod openapi;
 use clap::Subcommand;
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     /// create a new project
     New(new::New),
@@ -11,6 +12,9 @@ pub enum Commands {
     /// build openapi request api.
     Openapi(openapi::Openapi),
 
+    /// call an operation of a running service by name, like curl but aware of the openapi spec.
+    Call(openapi::Call),
+
     /// run a project.
     Start {
         #[clap(help = "Output file")]
@@ -59,6 +63,7 @@ impl Commands {
         match self {
             Commands::New(new) => new.run(),
             Commands::Openapi(openapi) => openapi.run(),
+            Commands::Call(call) => call.run(),
             _ => println!("Running command"),
         }
     }
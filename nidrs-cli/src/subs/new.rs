@@ -1,10 +1,5 @@
-use std::{
-    any,
-    io::{BufRead, Write},
-    path::PathBuf,
-};
+use std::path::PathBuf;
 
-use serde::de::Error;
 use toml::Table;
 
 use crate::shared::exec_cmd;
@@ -30,7 +25,7 @@ impl New {
         // 2. git clone template
         // 3. replace template name
         // 4. git init
-        let we = WorkEnv::new(std::env::current_dir().unwrap(), true).init();
+        let we = WorkEnv::new(std::env::current_dir().unwrap()).init();
 
         let is_init = self.target == "."; // is init mode
         let (project_path, project_name) = if let Some(name) = &self.name {
@@ -150,12 +145,11 @@ struct WorkEnv {
     cargo_toml: Option<PathBuf>,
     base_dir: PathBuf,
     is_init_git: bool,
-    is_root: bool,
     workspaces: Vec<WorkEnv>,
 }
 
 impl WorkEnv {
-    fn new(base_dir: PathBuf, is_root: bool) -> Self {
+    fn new(base_dir: PathBuf) -> Self {
         let cargo_toml = base_dir.join("Cargo.toml");
         let is_init_git = base_dir.join(".git").exists();
         let workspaces = vec![];
@@ -165,7 +159,6 @@ impl WorkEnv {
             base_dir,
             workspaces,
             is_init_git,
-            is_root: is_root,
         }
     }
 
@@ -180,7 +173,7 @@ impl WorkEnv {
                         for member in members {
                             let member = member.as_str().unwrap();
                             let member_path = self.base_dir.join(member);
-                            let we = WorkEnv::new(member_path, false);
+                            let we = WorkEnv::new(member_path);
                             self.mut_workspace().push(we.init());
                         }
                     }
@@ -188,25 +181,17 @@ impl WorkEnv {
             }
         }
 
-        return self;
+        self
     }
 
     fn base_dir(&self) -> &PathBuf {
         &self.base_dir
     }
 
-    fn is_root(&self) -> bool {
-        self.is_root
-    }
-
     fn is_init_git(&self) -> bool {
         self.is_init_git
     }
 
-    fn workspace(&self) -> &Vec<WorkEnv> {
-        self.workspaces.as_ref()
-    }
-
     fn mut_workspace(&mut self) -> &mut Vec<WorkEnv> {
         self.workspaces.as_mut()
     }
@@ -217,10 +202,4 @@ impl WorkEnv {
         let table: Table = toml::from_str(&content).unwrap();
         table
     }
-
-    fn write_cargo_toml(&self, table: Table) {
-        let cargo_toml = self.cargo_toml.as_ref().unwrap();
-        let content = toml::to_string(&table).unwrap();
-        std::fs::write(cargo_toml, content).unwrap();
-    }
 }
@@ -0,0 +1,324 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+
+use super::{OpenapiBuilder, HTTP_METHODS};
+
+/// The values copied as they are by `normalize`, a `null` in them is data.
+const LITERALS: [&str; 6] = ["example", "examples", "default", "enum", "const", "value"];
+
+/// Whether a spec file or url is yaml, from its extension.
+pub fn is_yaml(source: &str) -> bool {
+    let source = source.split(['?', '#']).next().unwrap_or_default();
+    source.ends_with(".yaml") || source.ends_with(".yml")
+}
+
+/// Parse a json or yaml spec, `source` tells which by its extension.
+pub fn parse_spec(text: &str, source: &str) -> Result<Value, anyhow::Error> {
+    if is_yaml(source) {
+        return serde_yaml::from_str(text).map_err(|e| anyhow::anyhow!("parse {}: {}", source, e));
+    }
+    serde_json::from_str(text).map_err(|e| anyhow::anyhow!("parse {}: {}", source, e))
+}
+
+/// Render a spec as json or yaml, by the extension of `file`, keys in a stable order.
+pub fn to_spec_text(openapi: &Value, file: &str) -> Result<String, anyhow::Error> {
+    if is_yaml(file) {
+        return Ok(serde_yaml::to_string(openapi)?);
+    }
+    Ok(serde_json::to_string_pretty(openapi)? + "\n")
+}
+
+/// Pulls the documents of external `$ref`s into `components`.
+#[derive(Default)]
+struct Bundler {
+    /// the parsed external documents
+    documents: HashMap<PathBuf, Value>,
+    /// the local ref of each bundled `(file, pointer)`
+    refs: HashMap<(PathBuf, String), String>,
+    /// the bundled components, by kind and name
+    components: Map<String, Value>,
+}
+
+impl Bundler {
+    /// Rewrite the refs of `value`, a part of the root document or of the external `document`.
+    fn rewrite(
+        &mut self,
+        value: &mut Value,
+        base: &Path,
+        document: Option<&Path>,
+        root: &Value,
+    ) -> Result<(), anyhow::Error> {
+        match value {
+            Value::Object(object) => {
+                if let Some(Value::String(r)) = object.get("$ref") {
+                    let (file, pointer) = r.split_once('#').unwrap_or((r.as_str(), ""));
+                    let file = match (file, document) {
+                        ("", None) => return Ok(()),
+                        ("", Some(document)) => document.to_path_buf(),
+                        (file, _) => normalize_path(&base.join(file)),
+                    };
+                    let local = self.bundle(file, pointer.to_string(), root)?;
+                    object.insert("$ref".to_string(), json!(local));
+                    return Ok(());
+                }
+                for value in object.values_mut() {
+                    self.rewrite(value, base, document, root)?;
+                }
+            }
+            Value::Array(values) => {
+                for value in values {
+                    self.rewrite(value, base, document, root)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The value an external ref points to, as it is in its document.
+    fn target(&mut self, file: &Path, pointer: &str) -> Result<Value, anyhow::Error> {
+        if !self.documents.contains_key(file) {
+            let text = std::fs::read_to_string(file)
+                .map_err(|e| anyhow::anyhow!("read {}: {}", file.display(), e))?;
+            let document = parse_spec(&text, &file.to_string_lossy())?;
+            self.documents.insert(file.to_path_buf(), document);
+        }
+        self.documents[file]
+            .pointer(pointer)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("$ref not found: {}#{}", file.display(), pointer))
+    }
+
+    /// Bundle the target of an external ref once, returns its local ref.
+    fn bundle(
+        &mut self,
+        file: PathBuf,
+        pointer: String,
+        root: &Value,
+    ) -> Result<String, anyhow::Error> {
+        let key = (file.clone(), pointer.clone());
+        if let Some(local) = self.refs.get(&key) {
+            return Ok(local.clone());
+        }
+
+        let mut target = self.target(&file, &pointer)?;
+
+        // `#/components/schemas/User` keeps its kind and name, anything else becomes a schema
+        let segments = pointer
+            .split('/')
+            .skip(1)
+            .map(|s| s.replace("~1", "/").replace("~0", "~"))
+            .collect::<Vec<_>>();
+        let (kind, name) = match segments.as_slice() {
+            [components, kind, name] if components == "components" => (kind.clone(), name.clone()),
+            [.., name] => ("schemas".to_string(), name.clone()),
+            [] => (
+                "schemas".to_string(),
+                file.file_stem()
+                    .map(|stem| {
+                        stem.to_string_lossy()
+                            .split('.')
+                            .next()
+                            .unwrap_or_default()
+                            .to_string()
+                    })
+                    .unwrap_or_default(),
+            ),
+        };
+        let mut unique = name.clone();
+        let mut n = 2;
+        while root["components"][&kind].get(&unique).is_some()
+            || self
+                .components
+                .get(&kind)
+                .and_then(|c| c.get(&unique))
+                .is_some()
+        {
+            unique = format!("{}{}", name, n);
+            n += 1;
+        }
+        let local = format!(
+            "#/components/{}/{}",
+            kind,
+            unique.replace('~', "~0").replace('/', "~1")
+        );
+        self.refs.insert(key, local.clone());
+        // reserve the name while the target is rewritten, it may refer back to itself
+        if let Value::Object(components) = self.components.entry(&kind).or_insert(json!({})) {
+            components.insert(unique.clone(), Value::Null);
+        }
+
+        let base = file.parent().unwrap_or(Path::new("")).to_path_buf();
+        self.rewrite(&mut target, &base, Some(&file), root)?;
+        self.components[&kind][&unique] = target;
+        Ok(local)
+    }
+}
+
+/// Resolve `.` and `..` without touching the file system, so one file has one key.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+impl OpenapiBuilder {
+    /// Pull the external `$ref`s of a spec read from a file in `base` into its `components`,
+    /// eg: `./common.yaml#/components/schemas/Error` -> `#/components/schemas/Error`.
+    pub fn bundle(&mut self, base: &Path) -> Result<(), anyhow::Error> {
+        let mut bundler = Bundler::default();
+        let root = self.openapi.clone();
+        // path items are inlined, they have no place in `components`
+        for item in self.openapi["paths"].as_object_mut().into_iter().flatten() {
+            let Some(r) = item.1["$ref"].as_str().filter(|r| !r.starts_with('#')) else {
+                continue;
+            };
+            let (file, pointer) = r.split_once('#').unwrap_or((r, ""));
+            let file = normalize_path(&base.join(file));
+            let mut target = bundler.target(&file, pointer)?;
+            bundler.rewrite(
+                &mut target,
+                file.parent().unwrap_or(Path::new("")),
+                Some(&file),
+                &root,
+            )?;
+            *item.1 = target;
+        }
+        bundler.rewrite(&mut self.openapi, base, None, &root)?;
+        for (kind, items) in bundler.components {
+            for (name, item) in items.as_object().into_iter().flatten() {
+                self.openapi["components"][&kind][name] = item.clone();
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowercase the methods of the paths and drop the `null` fields some generators emit,
+    /// keys are kept in a stable order by `serde_json`.
+    pub fn normalize(&mut self) {
+        if let Some(paths) = self.openapi["paths"].as_object_mut() {
+            for item in paths.values_mut() {
+                let Some(item) = item.as_object_mut() else {
+                    continue;
+                };
+                let methods = item
+                    .keys()
+                    .filter(|key| {
+                        *key != &key.to_lowercase()
+                            && HTTP_METHODS.contains(&key.to_lowercase().as_str())
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for method in methods {
+                    if let Some(opr) = item.remove(&method) {
+                        item.entry(method.to_lowercase()).or_insert(opr);
+                    }
+                }
+            }
+        }
+        drop_nulls(&mut self.openapi);
+    }
+}
+
+fn drop_nulls(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|key, value| !value.is_null() || LITERALS.contains(&key.as_str()));
+            for (key, value) in object.iter_mut() {
+                if !LITERALS.contains(&key.as_str()) {
+                    drop_nulls(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(drop_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle() {
+        let dir = std::env::temp_dir().join(format!("nid-bundle-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("schemas")).unwrap();
+        std::fs::write(
+            dir.join("schemas/common.yaml"),
+            "components:\n  schemas:\n    Error:\n      type: object\n      properties:\n        cause:\n          $ref: '#/components/schemas/Error'\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("schemas/user.json"),
+            r#"{ "type": "object", "properties": { "error": { "$ref": "./common.yaml#/components/schemas/Error" } } }"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.join("paths")).unwrap();
+        std::fs::write(
+            dir.join("paths/post.yaml"),
+            "get:\n  responses:\n    '200':\n      content:\n        application/json:\n          schema:\n            $ref: ../schemas/user.json\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("openapi.json"),
+            r#"{
+                "paths": { "/user": { "GET": {
+                    "description": null,
+                    "responses": { "200": { "content": { "application/json": {
+                        "schema": { "$ref": "schemas/user.json" }, "example": { "name": null }
+                    } } } }
+                } }, "/post": { "$ref": "./paths/post.yaml" } },
+                "components": { "schemas": { "user": { "type": "string", "default": null } } }
+            }"#,
+        )
+        .unwrap();
+
+        let builder = OpenapiBuilder::load(dir.join("openapi.json").to_str().unwrap()).unwrap();
+        let openapi = &builder.openapi;
+        let get = &openapi["paths"]["/user"]["get"];
+        assert!(get.get("description").is_none());
+        let media = &get["responses"]["200"]["content"]["application/json"];
+        assert_eq!(media["schema"]["$ref"], "#/components/schemas/user2");
+        assert_eq!(media["example"], json!({ "name": null }));
+        assert_eq!(
+            openapi["paths"]["/post"]["get"]["responses"]["200"]["content"]["application/json"]
+                ["schema"]["$ref"],
+            "#/components/schemas/user2"
+        );
+        assert_eq!(
+            openapi["components"]["schemas"]["user"]["default"],
+            Value::Null
+        );
+        assert!(openapi["components"]["schemas"]["user"]
+            .get("default")
+            .is_some());
+        assert_eq!(
+            openapi["components"]["schemas"]["user2"]["properties"]["error"]["$ref"],
+            "#/components/schemas/Error"
+        );
+        assert_eq!(
+            openapi["components"]["schemas"]["Error"]["properties"]["cause"]["$ref"],
+            "#/components/schemas/Error"
+        );
+
+        // the emitted spec loads back as it is
+        let yaml = dir.join("emitted.yaml");
+        std::fs::write(&yaml, to_spec_text(openapi, "emitted.yaml").unwrap()).unwrap();
+        let reloaded = OpenapiBuilder::load(yaml.to_str().unwrap()).unwrap();
+        assert_eq!(reloaded.openapi, *openapi);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,654 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use serde_json::Value;
+
+use super::{
+    api_path, contract::encode, merge::parse_key_value, naming::ts_member, OpenapiBuilder,
+    Operation,
+};
+
+/// Where `--save` keeps the profiles, in the home dir.
+const PROFILES_FILE: &str = ".nidrs/profiles.toml";
+
+/// eg: nid call user.get_one --path id=12 --query page=1 --body @body.json
+#[derive(clap::Parser, Debug)]
+pub struct Call {
+    /// the operation, as called on the generated client (`user.get_one`) or as `UserController.get_one`.
+    #[clap(required_unless_present_any = ["list", "completions", "save"])]
+    operation: Option<String>,
+
+    /// default: the server of the profile, or http://localhost:3000
+    #[clap(short, long)]
+    server: Option<String>,
+
+    /// default: the spec of the profile, or the spec served by the server
+    #[clap(long)]
+    spec: Option<String>,
+
+    /// a path param, eg: --path id=12
+    #[clap(long = "path", value_parser = parse_key_value)]
+    path: Vec<(String, String)>,
+
+    /// a query param, eg: --query page=1
+    #[clap(long = "query", value_parser = parse_key_value)]
+    query: Vec<(String, String)>,
+
+    /// a header, eg: -H x-tenant=acme
+    #[clap(short = 'H', long = "header", value_parser = parse_key_value)]
+    headers: Vec<(String, String)>,
+
+    /// the json body, or `@file` to read it from a file.
+    #[clap(short, long)]
+    body: Option<String>,
+
+    /// the credential of a security scheme, eg: --auth bearerAuth=TOKEN, basic auth as `user:password`.
+    #[clap(long = "auth", value_parser = parse_key_value)]
+    auth: Vec<(String, String)>,
+
+    /// the saved profile to use, default: `default` when it exists.
+    #[clap(short, long)]
+    profile: Option<String>,
+
+    /// save --server, --spec, --header and --auth into the profile,
+    /// the credentials are stored as they are in ~/.nidrs/profiles.toml, readable by you only.
+    #[clap(long)]
+    save: bool,
+
+    /// print the operations, one per line.
+    #[clap(long)]
+    list: bool,
+
+    /// print a completion script of the operation names.
+    #[clap(long, value_enum)]
+    completions: Option<Shell>,
+
+    /// print the response body as it is received.
+    #[clap(long)]
+    raw: bool,
+}
+
+/// The shells `--completions` writes a script for.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// What a profile remembers between calls.
+#[derive(Debug, Default, PartialEq)]
+struct Profile {
+    server: Option<String>,
+    spec: Option<String>,
+    headers: Vec<(String, String)>,
+    auth: Vec<(String, String)>,
+}
+
+impl Profile {
+    fn from_table(table: &toml::Table) -> Self {
+        let pairs = |key: &str| {
+            table
+                .get(key)
+                .and_then(|value| value.as_table())
+                .into_iter()
+                .flatten()
+                .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+                .collect()
+        };
+        Self {
+            server: table
+                .get("server")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            spec: table.get("spec").and_then(|v| v.as_str()).map(String::from),
+            headers: pairs("headers"),
+            auth: pairs("auth"),
+        }
+    }
+
+    fn to_table(&self) -> toml::Table {
+        let pairs = |pairs: &[(String, String)]| {
+            toml::Value::Table(
+                pairs
+                    .iter()
+                    .map(|(name, value)| (name.clone(), toml::Value::String(value.clone())))
+                    .collect(),
+            )
+        };
+        let mut table = toml::Table::new();
+        if let Some(server) = &self.server {
+            table.insert("server".to_string(), toml::Value::String(server.clone()));
+        }
+        if let Some(spec) = &self.spec {
+            table.insert("spec".to_string(), toml::Value::String(spec.clone()));
+        }
+        if !self.headers.is_empty() {
+            table.insert("headers".to_string(), pairs(&self.headers));
+        }
+        if !self.auth.is_empty() {
+            table.insert("auth".to_string(), pairs(&self.auth));
+        }
+        table
+    }
+
+    /// Overlay the given pairs, a pair with the same name replaces the saved one.
+    fn overlay(saved: &mut Vec<(String, String)>, given: &[(String, String)]) {
+        for (name, value) in given {
+            match saved.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+                Some(pair) => pair.1 = value.clone(),
+                None => saved.push((name.clone(), value.clone())),
+            }
+        }
+    }
+}
+
+fn profiles_path() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_default();
+    PathBuf::from(home).join(PROFILES_FILE)
+}
+
+fn read_profiles() -> Result<toml::Table, anyhow::Error> {
+    let path = profiles_path();
+    match std::fs::read_to_string(&path) {
+        Ok(text) => text
+            .parse()
+            .map_err(|e| anyhow::anyhow!("parse {}: {}", path.display(), e)),
+        Err(_) => Ok(toml::Table::new()),
+    }
+}
+
+/// Write the profiles readable by the user only, they hold credentials.
+fn write_profiles(path: &Path, text: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // the mode only applies to a new file, an older one may be readable by others
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(text.as_bytes())
+}
+
+/// The request of a call, checked against the operation.
+#[derive(Debug, Default)]
+struct Request {
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Option<Value>,
+}
+
+impl Call {
+    pub fn run(&self) {
+        if let Err(e) = self.call() {
+            eprintln!("[Call] {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    fn call(&self) -> Result<(), anyhow::Error> {
+        let mut profiles = read_profiles()?;
+        let name = self.profile.as_deref().unwrap_or("default");
+        let mut profile = match profiles.get(name) {
+            Some(toml::Value::Table(table)) => Profile::from_table(table),
+            _ if self.profile.is_some() && !self.save => {
+                return Err(anyhow::anyhow!(
+                    "no profile `{}` in {}",
+                    name,
+                    profiles_path().display()
+                ))
+            }
+            _ => Profile::default(),
+        };
+        profile.server = self.server.clone().or(profile.server);
+        profile.spec = self.spec.clone().or(profile.spec);
+        Profile::overlay(&mut profile.headers, &self.headers);
+        Profile::overlay(&mut profile.auth, &self.auth);
+
+        if self.save {
+            // a spec file is saved absolute, the profile is used from any dir
+            if let Some(spec) = profile.spec.as_mut() {
+                if let Ok(path) = std::fs::canonicalize(&*spec) {
+                    *spec = path.to_string_lossy().to_string();
+                }
+            }
+            profiles.insert(name.to_string(), toml::Value::Table(profile.to_table()));
+            let path = profiles_path();
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_profiles(&path, &toml::to_string(&profiles)?)?;
+            eprintln!("[Call] saved profile `{}` to {}", name, path.display());
+            if self.operation.is_none() {
+                return Ok(());
+            }
+        }
+
+        if let Some(shell) = self.completions {
+            print!("{}", completions(shell));
+            return Ok(());
+        }
+
+        let server = profile
+            .server
+            .clone()
+            .unwrap_or_else(|| "http://localhost:3000".to_string());
+        let builder = OpenapiBuilder::load(profile.spec.as_deref().unwrap_or(&server))?;
+        let operations = builder.operations();
+        if self.list {
+            let mut names = operations.iter().map(call_name).collect::<Vec<_>>();
+            names.sort();
+            println!("{}", names.join("\n"));
+            return Ok(());
+        }
+
+        let operation = self.operation.as_deref().unwrap_or_default();
+        let opr = operations
+            .iter()
+            .find(|opr| {
+                call_name(opr) == operation
+                    || format!("{}.{}", opr.controller, opr.router) == operation
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!("unknown operation `{}`, see `nid call --list`", operation)
+            })?;
+
+        let body = match self.body.as_deref() {
+            Some(body) => {
+                let text = match body.strip_prefix('@') {
+                    Some(file) => std::fs::read_to_string(file)
+                        .map_err(|e| anyhow::anyhow!("read {}: {}", file, e))?,
+                    None => body.to_string(),
+                };
+                Some(
+                    serde_json::from_str(&text)
+                        .map_err(|e| anyhow::anyhow!("--body is not json: {}", e))?,
+                )
+            }
+            None => None,
+        };
+        let mut request = builder
+            .call_request(opr, &self.path, &self.query, &profile.headers, body)
+            .map_err(|errors| anyhow::anyhow!("invalid arguments:\n  {}", errors.join("\n  ")))?;
+        builder.apply_auth(opr, &profile.auth, &mut request);
+
+        let mut url =
+            reqwest::Url::parse(&format!("{}{}", server.trim_end_matches('/'), request.path))?;
+        if !request.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&request.query);
+        }
+        let client = reqwest::blocking::Client::new();
+        let mut http = client.request(opr.method.to_uppercase().parse()?, url.clone());
+        for (name, value) in &request.headers {
+            http = http.header(name, value);
+        }
+        if let Some(body) = &request.body {
+            http = http
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_string());
+        }
+
+        let start = Instant::now();
+        let response = http.send()?;
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let text = response.text()?;
+        eprintln!(
+            "[Call] {} {} -> {} ({}ms)",
+            opr.method.to_uppercase(),
+            url,
+            status,
+            start.elapsed().as_millis()
+        );
+
+        let pretty = serde_json::from_str::<Value>(&text)
+            .ok()
+            .filter(|_| !self.raw && content_type.contains("json"))
+            .map(|value| serde_json::to_string_pretty(&value).unwrap());
+        println!("{}", pretty.unwrap_or(text.clone()));
+
+        for error in builder.check_response(opr.item, status.as_u16(), &content_type, &text) {
+            eprintln!("[Call] not as in the spec: {}", error);
+        }
+        if !status.is_success() {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
+
+/// The name of an operation on the command line, as called on the generated client, eg: `user.get_one`.
+fn call_name(opr: &Operation) -> String {
+    format!("{}.{}", api_path(opr.controller), ts_member(opr.router))
+}
+
+impl OpenapiBuilder {
+    /// Check the given params and body against the operation, every mismatch is reported.
+    fn call_request(
+        &self,
+        opr: &Operation,
+        path: &[(String, String)],
+        query: &[(String, String)],
+        headers: &[(String, String)],
+        body: Option<Value>,
+    ) -> Result<Request, Vec<String>> {
+        let parameters = self.parameters(opr);
+        let mut errors = vec![];
+        let mut request = Request {
+            path: opr.path.to_string(),
+            headers: headers.to_vec(),
+            ..Default::default()
+        };
+
+        for (location, given) in [("path", path), ("query", query), ("header", headers)] {
+            let declared = parameters
+                .iter()
+                .filter(|param| param["in"] == location)
+                .collect::<Vec<_>>();
+            for (name, raw) in given {
+                let Some(param) = declared.iter().find(|param| {
+                    let declared = param["name"].as_str().unwrap_or_default();
+                    // header names are case insensitive
+                    declared == name
+                        || (location == "header" && declared.eq_ignore_ascii_case(name))
+                }) else {
+                    // the profile may send headers the spec does not declare, eg: tracing
+                    if location != "header" {
+                        let names = declared
+                            .iter()
+                            .filter_map(|param| param["name"].as_str())
+                            .collect::<Vec<_>>();
+                        errors.push(format!(
+                            "unknown {} param `{}`, expected one of: {}",
+                            location,
+                            name,
+                            names.join(", ")
+                        ));
+                    }
+                    continue;
+                };
+                let schema = self.resolve(&param["schema"]);
+                match self.coerce(schema, raw) {
+                    Ok(value) => {
+                        errors.extend(self.validate(schema, &value).into_iter().map(|e| {
+                            let e = e.strip_prefix("$: ").unwrap_or(&e);
+                            format!("{} param `{}`: {}", location, name, e)
+                        }))
+                    }
+                    Err(e) => errors.push(format!("{} param `{}`: {}", location, name, e)),
+                }
+                match location {
+                    "path" => {
+                        request.path = request.path.replace(&format!("{{{}}}", name), &encode(raw))
+                    }
+                    "query" => request.query.push((name.clone(), raw.clone())),
+                    _ => {}
+                }
+            }
+            for param in declared {
+                let name = param["name"].as_str().unwrap_or_default();
+                let required = location == "path" || param["required"].as_bool().unwrap_or(false);
+                let present = given.iter().any(|(n, _)| {
+                    n == name || (location == "header" && n.eq_ignore_ascii_case(name))
+                });
+                if required && !present {
+                    errors.push(format!(
+                        "missing {} param `{}`, eg: --{} {}=...",
+                        location, name, location, name
+                    ));
+                }
+            }
+        }
+
+        let request_body = self.resolve(&opr.item["requestBody"]);
+        let schema = self
+            .response_media(request_body)
+            .map(|(_, media)| &media["schema"]);
+        match (&body, schema) {
+            (Some(body), Some(schema)) => errors.extend(
+                self.validate(schema, body)
+                    .into_iter()
+                    .map(|e| format!("body {}", e)),
+            ),
+            (Some(_), None) => errors.push("the operation takes no body".to_string()),
+            (None, Some(_)) if request_body["required"].as_bool().unwrap_or(false) => {
+                errors.push("missing the body, eg: --body @body.json".to_string())
+            }
+            (None, _) => {}
+        }
+        request.body = body;
+
+        if errors.is_empty() {
+            Ok(request)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Send the credentials of the first security alternative of the operation the profile has all of.
+    fn apply_auth(&self, opr: &Operation, auth: &[(String, String)], request: &mut Request) {
+        let security = self.security(opr.item);
+        if security.is_empty() {
+            return;
+        }
+        let credential = |name: &str| {
+            auth.iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let Some(names) = security
+            .iter()
+            .find(|names| names.iter().all(|name| credential(name).is_some()))
+        else {
+            eprintln!(
+                "[Call] no credentials for {}, see --auth",
+                security
+                    .iter()
+                    .map(|names| names.join(" + "))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            );
+            return;
+        };
+        let schemes = self.security_schemes();
+        for name in names {
+            let (Some((_, scheme)), Some(value)) =
+                (schemes.iter().find(|(n, _)| n == name), credential(name))
+            else {
+                continue;
+            };
+            let scheme_name = scheme["name"].as_str().unwrap_or_default().to_string();
+            match (scheme["type"].as_str(), scheme["in"].as_str()) {
+                (Some("apiKey"), Some("query")) => {
+                    request.query.push((scheme_name, value.to_string()))
+                }
+                (Some("apiKey"), Some("cookie")) => request
+                    .headers
+                    .push(("cookie".to_string(), format!("{}={}", scheme_name, value))),
+                (Some("apiKey"), _) => request.headers.push((scheme_name, value.to_string())),
+                (Some("http"), _)
+                    if scheme["scheme"]
+                        .as_str()
+                        .is_some_and(|s| s.eq_ignore_ascii_case("basic")) =>
+                {
+                    request.headers.push((
+                        "authorization".to_string(),
+                        format!("Basic {}", base64(value.as_bytes())),
+                    ))
+                }
+                _ => request
+                    .headers
+                    .push(("authorization".to_string(), format!("Bearer {}", value))),
+            }
+        }
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// A script completing the operation names of `nid call` from `nid call --list`.
+fn completions(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => {
+            r#"_nid_call() {
+  if [ "${COMP_WORDS[1]}" = "call" ] && [ "$COMP_CWORD" -eq 2 ]; then
+    COMPREPLY=($(compgen -W "$(nid call --list 2>/dev/null)" -- "${COMP_WORDS[2]}"))
+  fi
+}
+complete -o default -F _nid_call nid
+"#
+        }
+        Shell::Zsh => {
+            r#"#compdef nid
+_nid_call() {
+  if [ "$words[2]" = "call" ] && [ "$CURRENT" -eq 3 ]; then
+    compadd -- ${(f)"$(nid call --list 2>/dev/null)"}
+  else
+    _files
+  fi
+}
+compdef _nid_call nid
+"#
+        }
+        Shell::Fish => {
+            r#"complete -c nid -n '__fish_seen_subcommand_from call; and test (count (commandline -opc)) -eq 2' -f -a '(nid call --list 2>/dev/null)'
+"#
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_call_request() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/user/{id}": {
+                        "put": {
+                            "x-controller": "UserController",
+                            "x-router": "update",
+                            "security": [{ "apiKey": [] }, { "basic": [] }],
+                            "parameters": [
+                                { "name": "id", "in": "path", "required": true, "schema": { "type": "integer" } },
+                                { "name": "order", "in": "query", "schema": { "type": "string", "enum": ["asc", "desc"] } }
+                            ],
+                            "requestBody": {
+                                "required": true,
+                                "content": { "application/json": { "schema": { "type": "object", "required": ["name"] } } }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "securitySchemes": {
+                        "apiKey": { "type": "apiKey", "in": "query", "name": "key" },
+                        "basic": { "type": "http", "scheme": "basic" }
+                    }
+                }
+            }),
+        };
+        let operations = builder.operations();
+        let opr = &operations[0];
+        assert_eq!(call_name(opr), "user.update");
+
+        let pairs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let errors = builder
+            .call_request(
+                opr,
+                &[],
+                &pairs(&[("order", "up"), ("size", "2")]),
+                &[],
+                Some(json!({})),
+            )
+            .unwrap_err();
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert_eq!(errors[0], "missing path param `id`, eg: --path id=...");
+        assert_eq!(
+            errors[1],
+            "query param `order`: \"up\" is not one of [\"asc\",\"desc\"]"
+        );
+        assert_eq!(
+            errors[2],
+            "unknown query param `size`, expected one of: order"
+        );
+        assert_eq!(errors[3], "body $.name: is required");
+
+        let mut request = builder
+            .call_request(
+                opr,
+                &pairs(&[("id", "12")]),
+                &pairs(&[("order", "asc")]),
+                &[],
+                Some(json!({ "name": "wuma" })),
+            )
+            .unwrap();
+        assert_eq!(request.path, "/user/12");
+        builder.apply_auth(opr, &pairs(&[("basic", "user:pass")]), &mut request);
+        assert_eq!(
+            request.headers,
+            pairs(&[("authorization", "Basic dXNlcjpwYXNz")])
+        );
+
+        let profile = Profile {
+            server: Some("http://localhost:3000".to_string()),
+            auth: pairs(&[("apiKey", "k")]),
+            ..Default::default()
+        };
+        assert_eq!(Profile::from_table(&profile.to_table()), profile);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let path = std::env::temp_dir().join(format!("nid-profiles-{}", std::process::id()));
+            std::fs::write(&path, "").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+            write_profiles(&path, "[default]\n").unwrap();
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), "[default]\n");
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,448 @@
+use std::time::Instant;
+
+use serde_json::{json, Value};
+
+use super::{OpenapiBuilder, Operation};
+
+/// eg: nid openapi test http://localhost:3000 --fixtures ./contract.json
+#[derive(clap::Parser, Debug)]
+pub struct Test {
+    /// the nidrs server to test.
+    #[clap(default_value = "http://localhost:3000")]
+    server: String,
+
+    /// default: the spec served by the server
+    #[clap(short, long)]
+    spec: Option<String>,
+
+    /// requests keyed by `Controller.router`, operations with one are called whatever their method,
+    /// eg: `{ "UserController.create": { "body": { "name": "wuma" }, "status": 201 } }`
+    #[clap(short, long)]
+    fixtures: Option<String>,
+
+    /// print the report as json.
+    #[clap(long)]
+    json: bool,
+}
+
+/// A request of a fixtures file, what it leaves out is filled from the examples of the spec.
+#[derive(Debug, Default)]
+struct Fixture {
+    path: serde_json::Map<String, Value>,
+    query: serde_json::Map<String, Value>,
+    headers: serde_json::Map<String, Value>,
+    body: Option<Value>,
+    /// the expected status, default: any status the spec declares
+    status: Option<u16>,
+}
+
+impl Fixture {
+    fn from_value(value: &Value) -> Result<Self, String> {
+        let object = |key: &str| match &value[key] {
+            Value::Object(map) => Ok(map.clone()),
+            Value::Null => Ok(Default::default()),
+            _ => Err(format!("`{}` must be an object", key)),
+        };
+        if !value.is_object() {
+            return Err("expected an object".to_string());
+        }
+        Ok(Self {
+            path: object("path")?,
+            query: object("query")?,
+            headers: object("headers")?,
+            body: value.get("body").cloned(),
+            status: value["status"].as_u64().map(|status| status as u16),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    Fail,
+    Skip,
+}
+
+struct Case {
+    name: String,
+    method: String,
+    path: String,
+    status: Option<u16>,
+    outcome: Outcome,
+    errors: Vec<String>,
+    millis: u128,
+}
+
+impl Test {
+    pub fn run(&self) {
+        let builder = match OpenapiBuilder::load(self.spec.as_deref().unwrap_or(&self.server)) {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Openapi] {}", e);
+                std::process::exit(1);
+            }
+        };
+        let fixtures = match &self.fixtures {
+            Some(file) => match read_fixtures(file) {
+                Ok(fixtures) => fixtures,
+                Err(e) => {
+                    eprintln!("[Openapi] {}: {}", file, e);
+                    std::process::exit(1);
+                }
+            },
+            None => serde_json::Map::new(),
+        };
+
+        let client = reqwest::blocking::Client::new();
+        let mut cases = vec![];
+        for operations in builder.controllers().into_values() {
+            for opr in operations {
+                let name = format!("{}.{}", opr.controller, opr.router);
+                let fixtures = match fixtures.get(&name) {
+                    Some(Value::Array(items)) => items.clone(),
+                    Some(fixture) => vec![fixture.clone()],
+                    None => vec![],
+                };
+                if fixtures.is_empty() {
+                    // unsafe methods may change data, streams never end: call them only with a fixture
+                    let skip = opr.method != "get" || builder.stream_media(opr.item).is_some();
+                    cases.push(self.call(&client, &builder, &opr, &name, None, skip));
+                }
+                for fixture in fixtures {
+                    let fixture = match Fixture::from_value(&fixture) {
+                        Ok(fixture) => fixture,
+                        Err(e) => {
+                            eprintln!("[Openapi] fixture of {}: {}", name, e);
+                            std::process::exit(1);
+                        }
+                    };
+                    cases.push(self.call(&client, &builder, &opr, &name, Some(fixture), false));
+                }
+            }
+        }
+
+        let failed = cases.iter().any(|case| case.outcome == Outcome::Fail);
+        if self.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&to_json(&cases)).unwrap()
+            );
+        } else {
+            print_report(&cases);
+        }
+        if failed {
+            std::process::exit(1);
+        }
+    }
+
+    fn call(
+        &self,
+        client: &reqwest::blocking::Client,
+        builder: &OpenapiBuilder,
+        opr: &Operation,
+        name: &str,
+        fixture: Option<Fixture>,
+        skip: bool,
+    ) -> Case {
+        let mut case = Case {
+            name: name.to_string(),
+            method: opr.method.to_uppercase(),
+            path: opr.path.to_string(),
+            status: None,
+            outcome: Outcome::Skip,
+            errors: vec![],
+            millis: 0,
+        };
+        if skip {
+            return case;
+        }
+        let fixture = fixture.unwrap_or_default();
+
+        let request = builder.contract_request(opr, &fixture, &self.server);
+        let request = match request {
+            Ok((url, headers, body)) => {
+                case.path = url.path().to_string();
+                let mut request = client.request(opr.method.to_uppercase().parse().unwrap(), url);
+                for (name, value) in headers {
+                    request = request.header(name, value);
+                }
+                if let Some(body) = body {
+                    request = request
+                        .header(reqwest::header::CONTENT_TYPE, "application/json")
+                        .body(body.to_string());
+                }
+                request
+            }
+            Err(e) => {
+                case.outcome = Outcome::Fail;
+                case.errors.push(e);
+                return case;
+            }
+        };
+
+        let start = Instant::now();
+        let response = request.send();
+        case.millis = start.elapsed().as_millis();
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                case.outcome = Outcome::Fail;
+                case.errors.push(format!("request failed: {}", e));
+                return case;
+            }
+        };
+
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let body = response.text().unwrap_or_default();
+        case.status = Some(status);
+        if let Some(expected) = fixture.status.filter(|expected| *expected != status) {
+            case.errors
+                .push(format!("expected status {}, got {}", expected, status));
+        }
+        case.errors
+            .extend(builder.check_response(opr.item, status, &content_type, &body));
+        case.outcome = if case.errors.is_empty() {
+            Outcome::Pass
+        } else {
+            Outcome::Fail
+        };
+        case
+    }
+}
+
+impl OpenapiBuilder {
+    /// The url, headers and json body of a request to the operation, the fixture wins over the examples of the spec.
+    #[allow(clippy::type_complexity)]
+    fn contract_request(
+        &self,
+        opr: &Operation,
+        fixture: &Fixture,
+        server: &str,
+    ) -> Result<(reqwest::Url, Vec<(String, String)>, Option<Value>), String> {
+        let mut path = opr.path.to_string();
+        let mut query = vec![];
+        let mut headers = vec![];
+        for param in self.parameters(opr) {
+            let name = param["name"].as_str().unwrap_or_default();
+            let location = param["in"].as_str().unwrap_or_default();
+            let given = match location {
+                "path" => fixture.path.get(name),
+                "query" => fixture.query.get(name),
+                "header" => fixture.headers.get(name),
+                _ => None,
+            };
+            let required = param["required"].as_bool().unwrap_or(false);
+            // optional params are only sent when the fixture or the spec gives a value
+            let value = match given.or(param.get("example")) {
+                Some(value) => value.clone(),
+                None if required || location == "path" => self.sample(&param["schema"]),
+                None => continue,
+            };
+            let value = param_string(&value);
+            match location {
+                "path" => path = path.replace(&format!("{{{}}}", name), &encode(&value)),
+                "query" => query.push((name.to_string(), value)),
+                "header" => headers.push((name.to_string(), value)),
+                _ => {}
+            }
+        }
+        for (name, value) in &fixture.query {
+            if !query.iter().any(|(n, _)| n == name) {
+                query.push((name.clone(), param_string(value)));
+            }
+        }
+        for (name, value) in &fixture.headers {
+            if !headers.iter().any(|(n, _)| n == name) {
+                headers.push((name.clone(), param_string(value)));
+            }
+        }
+
+        let mut url = reqwest::Url::parse(&format!("{}{}", server.trim_end_matches('/'), path))
+            .map_err(|e| format!("invalid url {}{}: {}", server, path, e))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&query);
+        }
+
+        let body = fixture.body.clone().or_else(|| {
+            let request_body = self.resolve(&opr.item["requestBody"]);
+            self.response_media(request_body)
+                .filter(|(content_type, _)| content_type.contains("json"))
+                .map(|(_, media)| self.media_example(media))
+        });
+        Ok((url, headers, body))
+    }
+
+    /// Check a response against the responses of the operation, mismatches are reported with their json path.
+    pub fn check_response(
+        &self,
+        opr: &Value,
+        status: u16,
+        content_type: &str,
+        body: &str,
+    ) -> Vec<String> {
+        let responses = &opr["responses"];
+        let status_key = status.to_string();
+        let range_key = format!("{}XX", status / 100);
+        let Some(response) = [status_key.as_str(), range_key.as_str(), "default"]
+            .iter()
+            .find_map(|key| responses.get(*key))
+        else {
+            return vec![format!("status {} is not declared", status)];
+        };
+        let response = self.resolve(response);
+
+        let Some(content) = response["content"].as_object().filter(|c| !c.is_empty()) else {
+            return vec![];
+        };
+        let media_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        let Some(media) = content.get(&media_type).or_else(|| {
+            // `application/*` and `*/*` ranges of the spec
+            let range = format!("{}/*", media_type.split('/').next().unwrap_or_default());
+            content.get(&range).or(content.get("*/*"))
+        }) else {
+            return vec![format!(
+                "content type {:?} is not one of {}",
+                media_type,
+                content.keys().cloned().collect::<Vec<_>>().join(", ")
+            )];
+        };
+
+        if !media_type.contains("json") || media.get("schema").is_none() {
+            return vec![];
+        }
+        match serde_json::from_str::<Value>(body) {
+            Ok(value) => self.validate(&media["schema"], &value),
+            Err(e) => vec![format!("$: invalid json, {}", e)],
+        }
+    }
+}
+
+fn read_fixtures(file: &str) -> Result<serde_json::Map<String, Value>, anyhow::Error> {
+    let text = std::fs::read_to_string(file)?;
+    match serde_json::from_str(&text)? {
+        Value::Object(fixtures) => Ok(fixtures),
+        _ => Err(anyhow::anyhow!(
+            "expected an object keyed by `Controller.router`"
+        )),
+    }
+}
+
+/// A param value as sent on the wire, arrays are comma separated.
+fn param_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(param_string).collect::<Vec<_>>().join(","),
+        value => value.to_string(),
+    }
+}
+
+/// Percent-encode a path segment.
+pub fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn print_report(cases: &[Case]) {
+    for case in cases {
+        let label = match case.outcome {
+            Outcome::Pass => "PASS",
+            Outcome::Fail => "FAIL",
+            Outcome::Skip => "SKIP",
+        };
+        let status = case
+            .status
+            .map(|status| format!(" -> {} ({}ms)", status, case.millis))
+            .unwrap_or_default();
+        println!(
+            "[Test] {} {} {} {}{}",
+            label, case.name, case.method, case.path, status
+        );
+        for error in &case.errors {
+            println!("         {}", error);
+        }
+    }
+    let count = |outcome: Outcome| cases.iter().filter(|c| c.outcome == outcome).count();
+    println!(
+        "[Test] {} passed, {} failed, {} skipped",
+        count(Outcome::Pass),
+        count(Outcome::Fail),
+        count(Outcome::Skip)
+    );
+}
+
+fn to_json(cases: &[Case]) -> Value {
+    let cases = cases
+        .iter()
+        .map(|case| {
+            json!({
+                "operation": case.name,
+                "method": case.method,
+                "path": case.path,
+                "status": case.status,
+                "outcome": format!("{:?}", case.outcome).to_lowercase(),
+                "errors": case.errors,
+                "millis": case.millis,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({ "cases": cases })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_response() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "components": { "schemas": { "UserDto": {
+                    "type": "object",
+                    "required": ["id"],
+                    "properties": { "id": { "type": "integer" }, "tags": { "type": "array", "items": { "type": "string" } } }
+                } } }
+            }),
+        };
+        let opr = json!({
+            "responses": {
+                "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/UserDto" } } } },
+                "4XX": { "description": "client error" }
+            }
+        });
+        let check =
+            |status, content_type, body| builder.check_response(&opr, status, content_type, body);
+
+        assert!(check(200, "application/json; charset=utf-8", r#"{"id":1}"#).is_empty());
+        assert_eq!(
+            check(200, "application/json", r#"{"tags":["a",2]}"#),
+            vec![
+                "$.id: is required",
+                "$.tags[1]: expected string, got integer"
+            ]
+        );
+        assert!(check(404, "text/plain", "not found").is_empty());
+        assert_eq!(check(500, "", ""), vec!["status 500 is not declared"]);
+        assert_eq!(
+            check(200, "text/html", "<p>"),
+            vec!["content type \"text/html\" is not one of application/json"]
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use super::{OpenapiBuilder, Operation};
+
+/// What an operation is checked for, in the order of the report.
+const CHECKS: [(&str, &str); 5] = [
+    ("description", "Description"),
+    ("body", "Typed body"),
+    ("response", "Typed response"),
+    ("examples", "Examples"),
+    ("errors", "Error responses"),
+];
+
+/// eg: nid openapi coverage ./openapi.json --min 80
+#[derive(clap::Parser, Debug)]
+pub struct Coverage {
+    /// nidrs server address, openapi spec file or nidrs project dir.
+    #[clap(default_value = "http://localhost:3000")]
+    spec: String,
+
+    /// print the report as json.
+    #[clap(long)]
+    json: bool,
+
+    /// exit 1 when the overall coverage is below this percentage.
+    #[clap(long)]
+    min: Option<f64>,
+}
+
+/// The covered and applicable operations of each check.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Tally {
+    operations: usize,
+    checks: [(usize, usize); 5],
+}
+
+impl Tally {
+    fn add(&mut self, checks: &[Option<bool>; 5]) {
+        self.operations += 1;
+        for (tally, check) in self.checks.iter_mut().zip(checks) {
+            if let Some(covered) = check {
+                tally.0 += *covered as usize;
+                tally.1 += 1;
+            }
+        }
+    }
+
+    /// All the checks together, the ones which do not apply left out.
+    fn total(&self) -> (usize, usize) {
+        self.checks.iter().fold((0, 0), |(covered, total), check| {
+            (covered + check.0, total + check.1)
+        })
+    }
+}
+
+impl Coverage {
+    pub fn run(&self) {
+        let builder = match OpenapiBuilder::load(&self.spec) {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Coverage] {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let (controllers, overall) = builder.coverage();
+        let percent = percent(overall.total()).unwrap_or(100.0);
+        let pass = self.min.is_none_or(|min| percent >= min);
+
+        if self.json {
+            let mut report = to_json(&controllers, &overall);
+            report["min"] = json!(self.min);
+            report["pass"] = json!(pass);
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        } else {
+            print!("{}", to_table(&controllers, &overall));
+        }
+
+        if !pass {
+            eprintln!(
+                "[Coverage] {:.1}% is below the minimum of {}%",
+                percent,
+                self.min.unwrap_or_default()
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+impl OpenapiBuilder {
+    /// The tally of each controller and of all operations.
+    fn coverage(&self) -> (BTreeMap<&str, Tally>, Tally) {
+        let mut controllers = BTreeMap::new();
+        let mut overall = Tally::default();
+        for (controller, operations) in self.controllers() {
+            let mut tally = Tally::default();
+            for opr in &operations {
+                let checks = self.coverage_checks(opr);
+                tally.add(&checks);
+                overall.add(&checks);
+            }
+            controllers.insert(controller, tally);
+        }
+        (controllers, overall)
+    }
+
+    /// Whether the operation covers each check, `None` when it does not apply.
+    ///
+    /// The body and response are typed when `to_ts` renders them as something else than `any`.
+    fn coverage_checks(&self, opr: &Operation) -> [Option<bool>; 5] {
+        let item = opr.item;
+        let description = [&item["summary"], &item["description"]]
+            .iter()
+            .any(|text| text.as_str().is_some_and(|t| !t.trim().is_empty()));
+
+        let request_body = self.resolve(&item["requestBody"]);
+        let request_media = self.response_media(request_body);
+        let body = request_media.map(|(_, media)| self.is_typed(&media["schema"]));
+
+        let (_, response) = self.success_response(item);
+        let response_media = self.response_media(response);
+        let typed_response = match self.stream_media(item) {
+            Some((_, _, schema)) => Some(self.is_typed(schema)),
+            // no content, eg: 204
+            None if response.is_object() && response_media.is_none() => None,
+            None => Some(response_media.is_some_and(|(_, media)| self.is_typed(&media["schema"]))),
+        };
+
+        let examples = self
+            .parameters(opr)
+            .iter()
+            .any(|param| has_example(param) || has_example(self.resolve(&param["schema"])))
+            || request_media
+                .into_iter()
+                .chain(response_media)
+                .any(|(_, media)| {
+                    has_example(media) || has_example(self.resolve(&media["schema"]))
+                });
+
+        let errors = item["responses"].as_object().is_some_and(|responses| {
+            responses
+                .keys()
+                .any(|code| code.starts_with('4') || code.starts_with('5') || code == "default")
+        });
+
+        [
+            Some(description),
+            body,
+            typed_response,
+            Some(examples),
+            Some(errors),
+        ]
+    }
+
+    fn is_typed(&self, schema: &Value) -> bool {
+        schema.is_object() && self.ts_type(schema) != "any"
+    }
+}
+
+fn has_example(value: &Value) -> bool {
+    value.get("example").is_some()
+        || value["examples"]
+            .as_object()
+            .is_some_and(|examples| !examples.is_empty())
+        || value["examples"]
+            .as_array()
+            .is_some_and(|examples| !examples.is_empty())
+}
+
+fn percent((covered, total): (usize, usize)) -> Option<f64> {
+    (total > 0).then(|| covered as f64 * 100.0 / total as f64)
+}
+
+fn cell(check: (usize, usize)) -> String {
+    match percent(check) {
+        Some(percent) => format!("{:.0}% ({}/{})", percent, check.0, check.1),
+        None => "-".to_string(),
+    }
+}
+
+fn to_table(controllers: &BTreeMap<&str, Tally>, overall: &Tally) -> String {
+    let mut header = vec!["Controller".to_string(), "Operations".to_string()];
+    header.extend(CHECKS.iter().map(|(_, title)| title.to_string()));
+    header.push("Total".to_string());
+
+    let row = |name: &str, tally: &Tally| {
+        let mut row = vec![name.to_string(), tally.operations.to_string()];
+        row.extend(tally.checks.iter().map(|check| cell(*check)));
+        row.push(cell(tally.total()));
+        row
+    };
+    let mut rows = vec![header];
+    rows.extend(
+        controllers
+            .iter()
+            .map(|(controller, tally)| row(controller, tally)),
+    );
+    rows.push(row("Overall", overall));
+
+    let widths = (0..rows[0].len())
+        .map(|i| rows.iter().map(|row| row[i].len()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    let mut table = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        // the overall row is set apart like the header
+        if i == 1 || i == rows.len() - 1 {
+            let rule = widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>();
+            table.push_str(rule.join("  ").trim_end());
+            table.push('\n');
+        }
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+fn to_json(controllers: &BTreeMap<&str, Tally>, overall: &Tally) -> Value {
+    let tally_json = |tally: &Tally| {
+        let mut report = Map::new();
+        report.insert("operations".to_string(), json!(tally.operations));
+        for ((key, _), check) in CHECKS.iter().zip(tally.checks) {
+            report.insert(
+                key.to_string(),
+                json!({ "covered": check.0, "total": check.1, "percent": percent(check) }),
+            );
+        }
+        let total = tally.total();
+        report.insert(
+            "total".to_string(),
+            json!({ "covered": total.0, "total": total.1, "percent": percent(total) }),
+        );
+        Value::Object(report)
+    };
+    json!({
+        "controllers": controllers
+            .iter()
+            .map(|(controller, tally)| (controller.to_string(), tally_json(tally)))
+            .collect::<Map<_, _>>(),
+        "overall": tally_json(overall),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coverage() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/user/{id}": {
+                        "get": {
+                            "x-controller": "UserController",
+                            "x-router": "get_one",
+                            "summary": "get a user",
+                            "parameters": [{ "name": "id", "in": "path", "schema": { "type": "integer" }, "example": 1 }],
+                            "responses": {
+                                "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/UserDto" } } } },
+                                "404": { "description": "not found" }
+                            }
+                        },
+                        "put": {
+                            "x-controller": "UserController",
+                            "x-router": "update",
+                            "requestBody": { "content": { "application/json": { "schema": {} } } },
+                            "responses": { "204": { "description": "updated" } }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": { "UserDto": { "type": "object", "properties": { "id": { "type": "integer" } } } }
+                }
+            }),
+        };
+
+        let (controllers, overall) = builder.coverage();
+        assert_eq!(overall.operations, 2);
+        // description, body, response, examples, errors
+        assert_eq!(overall.checks, [(1, 2), (0, 1), (1, 1), (1, 2), (1, 2)]);
+        assert_eq!(overall.total(), (4, 8));
+        assert_eq!(controllers["UserController"], overall);
+
+        let table = to_table(&controllers, &overall);
+        assert!(table.starts_with("Controller      Operations  Description"));
+        assert!(table.contains("\nOverall         2           50% (1/2)    0% (0/1)"));
+        assert_eq!(
+            to_json(&controllers, &overall)["overall"]["total"]["percent"],
+            50.0
+        );
+    }
+}
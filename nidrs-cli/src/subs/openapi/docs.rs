@@ -0,0 +1,492 @@
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use super::{schema::schema_type, OpenapiBuilder, Operation};
+
+/// eg: nid openapi docs ./openapi.json --format html --out ./docs
+#[derive(clap::Parser, Debug)]
+pub struct Docs {
+    /// nidrs server address or openapi spec file.
+    #[clap(default_value = "http://localhost:3000")]
+    spec: String,
+
+    #[clap(short, long, value_enum, default_value = "markdown")]
+    format: DocsFormat,
+
+    #[clap(short, long, default_value = "./docs")]
+    out: String,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocsFormat {
+    Markdown,
+    Html,
+}
+
+/// The content of a page, rendered as markdown or html.
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    Code(String),
+    Table(Vec<&'static str>, Vec<Vec<String>>),
+    Links(Vec<(usize, String, String)>),
+}
+
+impl Docs {
+    pub fn run(&self) {
+        let builder = match OpenapiBuilder::load(&self.spec) {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Docs] {}", e);
+                std::process::exit(1);
+            }
+        };
+        let out_dir = PathBuf::from(&self.out);
+        if let Err(e) = std::fs::create_dir_all(&out_dir) {
+            eprintln!("[Docs] create {:?}: {}", out_dir, e);
+            std::process::exit(1);
+        }
+
+        let ext = match self.format {
+            DocsFormat::Markdown => "md",
+            DocsFormat::Html => "html",
+        };
+        let title = builder.openapi["info"]["title"]
+            .as_str()
+            .unwrap_or("API")
+            .to_string();
+
+        let mut pages = vec![];
+        let mut toc = vec![];
+        for (controller, operations) in builder.controllers() {
+            let file = format!("{}.{}", controller, ext);
+            toc.push((0, controller.to_string(), file.clone()));
+            for opr in &operations {
+                toc.push((
+                    1,
+                    opr.router.to_string(),
+                    format!("{}#{}", file, opr.router),
+                ));
+            }
+            pages.push((
+                file,
+                controller.to_string(),
+                builder.controller_blocks(controller, &operations),
+            ));
+        }
+
+        let mut index = vec![Block::Heading(1, title.clone())];
+        if let Some(version) = builder.openapi["info"]["version"].as_str() {
+            index.push(Block::Paragraph(format!("Version: `{}`", version)));
+        }
+        if let Some(description) = builder.openapi["info"]["description"].as_str() {
+            index.push(Block::Paragraph(description.to_string()));
+        }
+        index.push(Block::Heading(2, "Contents".to_string()));
+        index.push(Block::Links(toc));
+        let index_file = match self.format {
+            DocsFormat::Markdown => "README.md".to_string(),
+            DocsFormat::Html => "index.html".to_string(),
+        };
+        pages.push((index_file, title.clone(), index));
+
+        for (file, page_title, blocks) in &pages {
+            let content = match self.format {
+                DocsFormat::Markdown => to_markdown(blocks),
+                DocsFormat::Html => to_html(&title, page_title, blocks),
+            };
+            let path = out_dir.join(file);
+            if let Err(e) = std::fs::write(&path, content) {
+                eprintln!("[Docs] write {:?}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+
+        println!("[Docs] {} pages -> {:?}", pages.len(), out_dir);
+    }
+}
+
+impl OpenapiBuilder {
+    fn controller_blocks(&self, controller: &str, operations: &[Operation]) -> Vec<Block> {
+        let mut blocks = vec![Block::Heading(1, controller.to_string())];
+        blocks.push(Block::Links(
+            operations
+                .iter()
+                .map(|opr| (0, opr.router.to_string(), format!("#{}", opr.router)))
+                .collect(),
+        ));
+
+        for opr in operations {
+            blocks.push(Block::Heading(2, opr.router.to_string()));
+            let deprecated = if opr.item["deprecated"].as_bool().unwrap_or(false) {
+                " **deprecated**"
+            } else {
+                ""
+            };
+            blocks.push(Block::Paragraph(format!(
+                "`{} {}`{}",
+                opr.method.to_uppercase(),
+                opr.path,
+                deprecated
+            )));
+            for text in [&opr.item["summary"], &opr.item["description"]] {
+                if let Some(text) = text.as_str() {
+                    blocks.push(Block::Paragraph(text.to_string()));
+                }
+            }
+
+            let parameters = self.parameters(opr);
+            if !parameters.is_empty() {
+                blocks.push(Block::Heading(3, "Parameters".to_string()));
+                let rows = parameters
+                    .iter()
+                    .map(|param| {
+                        vec![
+                            format!("`{}`", param["name"].as_str().unwrap_or_default()),
+                            param["in"].as_str().unwrap_or_default().to_string(),
+                            self.type_label(&param["schema"]),
+                            yes_no(param["required"].as_bool().unwrap_or(false)),
+                            describe(param, self.resolve(&param["schema"])),
+                        ]
+                    })
+                    .collect();
+                blocks.push(Block::Table(
+                    vec!["Name", "In", "Type", "Required", "Description"],
+                    rows,
+                ));
+            }
+
+            let request_body = self.resolve(&opr.item["requestBody"]);
+            if let Some((content_type, media)) = self.response_media(request_body) {
+                blocks.push(Block::Heading(
+                    3,
+                    format!("Request body `{}`", content_type),
+                ));
+                if let Some(description) = request_body["description"].as_str() {
+                    blocks.push(Block::Paragraph(description.to_string()));
+                }
+                self.schema_blocks(&media["schema"], &mut blocks);
+                blocks.push(Block::Code(pretty(&self.media_example(media))));
+            }
+
+            if let Some(responses) = opr.item["responses"].as_object() {
+                blocks.push(Block::Heading(3, "Responses".to_string()));
+                let rows = responses
+                    .iter()
+                    .map(|(code, response)| {
+                        let response = self.resolve(response);
+                        vec![
+                            format!("`{}`", code),
+                            self.response_media(response)
+                                .map(|(content_type, _)| format!("`{}`", content_type))
+                                .unwrap_or_default(),
+                            response["description"]
+                                .as_str()
+                                .unwrap_or_default()
+                                .to_string(),
+                        ]
+                    })
+                    .collect();
+                blocks.push(Block::Table(
+                    vec!["Status", "Content type", "Description"],
+                    rows,
+                ));
+
+                let (status, response) = self.success_response(opr.item);
+                if let Some((_, media)) = self.response_media(response) {
+                    blocks.push(Block::Heading(4, format!("{} response", status)));
+                    self.schema_blocks(&media["schema"], &mut blocks);
+                    blocks.push(Block::Code(pretty(&self.media_example(media))));
+                }
+            }
+        }
+
+        blocks
+    }
+
+    /// A table of the schema properties, nested objects are flattened as `user.name`.
+    fn schema_blocks(&self, schema: &Value, blocks: &mut Vec<Block>) {
+        let mut rows = vec![];
+        self.property_rows("", schema, &mut rows, 0);
+        if rows.is_empty() {
+            blocks.push(Block::Paragraph(format!(
+                "Type: {}",
+                self.type_label(schema)
+            )));
+        } else {
+            blocks.push(Block::Table(
+                vec!["Field", "Type", "Required", "Description"],
+                rows,
+            ));
+        }
+    }
+
+    fn property_rows(
+        &self,
+        prefix: &str,
+        schema: &Value,
+        rows: &mut Vec<Vec<String>>,
+        depth: usize,
+    ) {
+        let schema = self.resolve(schema);
+        if depth > 4 {
+            return;
+        }
+        if schema_type(schema) == "array" {
+            self.property_rows(&format!("{}[]", prefix), &schema["items"], rows, depth + 1);
+            return;
+        }
+        let Some(properties) = schema["properties"].as_object() else {
+            return;
+        };
+        for (name, property) in properties {
+            let required = schema["required"]
+                .as_array()
+                .map(|r| r.iter().any(|r| r == name))
+                .unwrap_or(false);
+            let field = if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            rows.push(vec![
+                format!("`{}`", field),
+                self.type_label(property),
+                yes_no(required),
+                describe(property, self.resolve(property)),
+            ]);
+            self.property_rows(&field, property, rows, depth + 1);
+        }
+    }
+
+    /// A short label of the schema type, eg: `array<UserDto>`, `string(email)`, `"a" | "b"`.
+    fn type_label(&self, schema: &Value) -> String {
+        if let Some(name) = schema["$ref"]
+            .as_str()
+            .and_then(|r| r.strip_prefix("#/components/schemas/"))
+        {
+            return name.to_string();
+        }
+        let schema = self.resolve(schema);
+        if let Some(values) = schema["enum"].as_array() {
+            return values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(" | ");
+        }
+        match schema_type(schema) {
+            "" => "any".to_string(),
+            "array" => format!("array<{}>", self.type_label(&schema["items"])),
+            t => match schema["format"].as_str() {
+                Some(format) => format!("{}({})", t, format),
+                None => t.to_string(),
+            },
+        }
+    }
+}
+
+/// The description of a parameter or property, with its deprecation, default and example.
+fn describe(item: &Value, schema: &Value) -> String {
+    let mut text = item["description"]
+        .as_str()
+        .or(schema["description"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    if item["deprecated"].as_bool().unwrap_or(false)
+        || schema["deprecated"].as_bool().unwrap_or(false)
+    {
+        text.push_str(" **deprecated**");
+    }
+    if let Some(default) = schema.get("default") {
+        text.push_str(&format!(" Default: `{}`", default));
+    }
+    if let Some(example) = item.get("example").or(schema.get("example")) {
+        text.push_str(&format!(" Example: `{}`", example));
+    }
+    text.trim().to_string()
+}
+
+fn yes_no(b: bool) -> String {
+    if b { "yes" } else { "no" }.to_string()
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap()
+}
+
+fn to_markdown(blocks: &[Block]) -> String {
+    let mut md = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                md.push_str(&format!("{} {}\n\n", "#".repeat(*level), text))
+            }
+            Block::Paragraph(text) => md.push_str(&format!("{}\n\n", text)),
+            Block::Code(code) => md.push_str(&format!("```json\n{}\n```\n\n", code)),
+            Block::Table(headers, rows) => {
+                md.push_str(&format!("| {} |\n", headers.join(" | ")));
+                md.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+                for row in rows {
+                    let cells = row
+                        .iter()
+                        .map(|cell| cell.replace('|', "\\|").replace('\n', "<br>"))
+                        .collect::<Vec<_>>();
+                    md.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+                md.push('\n');
+            }
+            Block::Links(links) => {
+                for (indent, text, href) in links {
+                    md.push_str(&format!("{}- [{}]({})\n", "  ".repeat(*indent), text, href));
+                }
+                md.push('\n');
+            }
+        }
+    }
+    md
+}
+
+fn to_html(site: &str, title: &str, blocks: &[Block]) -> String {
+    let mut body = String::new();
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                let id = if *level == 2 {
+                    format!(" id=\"{}\"", escape_html(text))
+                } else {
+                    String::new()
+                };
+                body.push_str(&format!("<h{level}{id}>{}</h{level}>\n", inline_html(text)));
+            }
+            Block::Paragraph(text) => body.push_str(&format!("<p>{}</p>\n", inline_html(text))),
+            Block::Code(code) => {
+                body.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(code)))
+            }
+            Block::Table(headers, rows) => {
+                body.push_str("<table>\n<tr>");
+                for header in headers {
+                    body.push_str(&format!("<th>{}</th>", header));
+                }
+                body.push_str("</tr>\n");
+                for row in rows {
+                    body.push_str("<tr>");
+                    for cell in row {
+                        body.push_str(&format!("<td>{}</td>", inline_html(cell)));
+                    }
+                    body.push_str("</tr>\n");
+                }
+                body.push_str("</table>\n");
+            }
+            Block::Links(links) => {
+                body.push_str("<ul>\n");
+                for (indent, text, href) in links {
+                    body.push_str(&format!(
+                        "<li class=\"level-{}\"><a href=\"{}\">{}</a></li>\n",
+                        indent,
+                        escape_html(&html_href(href)),
+                        escape_html(text)
+                    ));
+                }
+                body.push_str("</ul>\n");
+            }
+        }
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title} - {site}</title>
+<style>
+body {{ font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; max-width: 960px; margin: 0 auto; padding: 24px; color: #222; }}
+nav a {{ color: #555; }}
+code {{ background: #f3f3f3; padding: 1px 4px; border-radius: 3px; }}
+pre {{ background: #f6f8fa; padding: 12px; overflow: auto; }}
+pre code {{ background: none; padding: 0; }}
+table {{ border-collapse: collapse; width: 100%; margin-bottom: 16px; }}
+th, td {{ border: 1px solid #ddd; padding: 6px 8px; text-align: left; vertical-align: top; }}
+li.level-1 {{ margin-left: 20px; }}
+h2 {{ border-top: 1px solid #eee; padding-top: 16px; }}
+</style>
+</head>
+<body>
+<nav><a href="index.html">{site}</a></nav>
+{body}</body>
+</html>
+"#,
+        title = escape_html(title),
+        site = escape_html(site),
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escape the text and render the `code` and **strong** spans of the markdown blocks.
+fn inline_html(text: &str) -> String {
+    let mut html = String::new();
+    for (i, part) in escape_html(text).split('`').enumerate() {
+        if i % 2 == 1 {
+            html.push_str(&format!("<code>{}</code>", part));
+        } else {
+            for (j, part) in part.split("**").enumerate() {
+                if j % 2 == 1 {
+                    html.push_str(&format!("<strong>{}</strong>", part));
+                } else {
+                    html.push_str(part);
+                }
+            }
+        }
+    }
+    html.replace('\n', "<br>")
+}
+
+/// Point a link to a markdown page at its html page, eg: `user.md#get_one` -> `user.html#get_one`.
+fn html_href(href: &str) -> String {
+    let (file, anchor) = match href.split_once('#') {
+        Some((file, anchor)) => (file, format!("#{}", anchor)),
+        None => (href, String::new()),
+    };
+    match file.strip_suffix(".md") {
+        Some(stem) => format!("{}.html{}", stem, anchor),
+        None => href.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let blocks = vec![
+            Block::Paragraph("`GET /user` **deprecated**".to_string()),
+            Block::Table(vec!["Type"], vec![vec!["\"a\" | \"b\"".to_string()]]),
+        ];
+        assert_eq!(
+            to_markdown(&blocks),
+            "`GET /user` **deprecated**\n\n| Type |\n| --- |\n| \"a\" \\| \"b\" |\n\n"
+        );
+        let html = to_html("api", "user", &blocks);
+        assert!(html.contains("<p><code>GET /user</code> <strong>deprecated</strong></p>"));
+        assert!(html.contains("<td>&quot;a&quot; | &quot;b&quot;</td>"));
+
+        assert_eq!(
+            html_href("UserController.md#get_one"),
+            "UserController.html#get_one"
+        );
+        assert_eq!(html_href("README.md"), "README.html");
+        assert_eq!(
+            html_href("docs.md.UserController.html"),
+            "docs.md.UserController.html"
+        );
+        assert_eq!(html_href("#get_one"), "#get_one");
+    }
+}
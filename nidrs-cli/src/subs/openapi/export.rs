@@ -0,0 +1,451 @@
+use serde_json::{json, Value};
+
+use super::{bundle::is_yaml, servers::server_url, OpenapiBuilder, Operation};
+
+/// eg: nid openapi export ./openapi.json --format postman --out ./api.postman_collection.json
+#[derive(clap::Parser, Debug)]
+pub struct Export {
+    /// nidrs server address or openapi spec file.
+    #[clap(default_value = "http://localhost:3000")]
+    spec: String,
+
+    #[clap(short, long, value_enum, default_value = "postman")]
+    format: ExportFormat,
+
+    /// default: print to stdout
+    #[clap(short, long)]
+    out: Option<String>,
+
+    /// default: the first of `servers`, or the nidrs server address
+    #[clap(short, long)]
+    base_url: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Postman collection v2.1
+    Postman,
+    /// Insomnia export v4
+    Insomnia,
+    /// `.http` file for the REST Client / JetBrains HTTP Client
+    Http,
+}
+
+/// A request of the collection, pre-filled with examples of the spec.
+struct Request<'a> {
+    opr: Operation<'a>,
+    description: String,
+    path_params: Vec<(String, String)>,
+    query: Vec<Param>,
+    headers: Vec<Param>,
+    body: Option<(String, Value)>,
+}
+
+struct Param {
+    name: String,
+    value: String,
+    required: bool,
+    description: String,
+}
+
+impl Export {
+    pub fn run(&self) {
+        let builder = match OpenapiBuilder::load(&self.spec) {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Export] {}", e);
+                std::process::exit(1);
+            }
+        };
+        let base_url = self
+            .base_url
+            .clone()
+            .unwrap_or_else(|| builder.base_url(&self.spec));
+
+        let requests = builder.requests();
+        let content = match self.format {
+            ExportFormat::Postman => {
+                serde_json::to_string_pretty(&to_postman(&builder, &requests, &base_url)).unwrap()
+            }
+            ExportFormat::Insomnia => {
+                serde_json::to_string_pretty(&to_insomnia(&builder, &requests, &base_url)).unwrap()
+            }
+            ExportFormat::Http => to_http(&requests, &base_url),
+        };
+
+        match &self.out {
+            Some(out) => {
+                if let Err(e) = std::fs::write(out, content) {
+                    eprintln!("[Export] write {}: {}", out, e);
+                    std::process::exit(1);
+                }
+                println!("[Export] {} requests -> {}", requests.len(), out);
+            }
+            None => println!("{}", content),
+        }
+    }
+}
+
+impl OpenapiBuilder {
+    /// The first server of the spec with its variables filled, a relative one is joined to
+    /// the nidrs server address the spec is read from, eg: `/api` -> `http://localhost:3000/api`.
+    fn base_url(&self, spec: &str) -> String {
+        let origin = if spec.starts_with("http") && !spec.ends_with(".json") && !is_yaml(spec) {
+            spec.trim_end_matches('/').to_string()
+        } else {
+            "http://localhost:3000".to_string()
+        };
+        match self.environments().first() {
+            Some((_, server)) => {
+                let url = server_url(server);
+                if url.starts_with("http://") || url.starts_with("https://") {
+                    url
+                } else {
+                    format!("{}/{}", origin, url.trim_start_matches('/'))
+                        .trim_end_matches('/')
+                        .to_string()
+                }
+            }
+            None => origin,
+        }
+    }
+
+    /// The operations as requests, grouped by controller order.
+    fn requests(&self) -> Vec<Request<'_>> {
+        let mut requests = vec![];
+        for operations in self.controllers().into_values() {
+            for opr in operations {
+                let mut request = Request {
+                    opr,
+                    description: [&opr.item["summary"], &opr.item["description"]]
+                        .iter()
+                        .filter_map(|text| text.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n\n"),
+                    path_params: vec![],
+                    query: vec![],
+                    headers: vec![],
+                    body: None,
+                };
+
+                for param in self.parameters(&opr) {
+                    let location = param["in"].as_str().unwrap_or_default();
+                    let name = param["name"].as_str().unwrap_or_default().to_string();
+                    let value = param
+                        .get("example")
+                        .cloned()
+                        .unwrap_or_else(|| self.sample(&param["schema"]));
+                    let value = match value {
+                        Value::String(s) => s,
+                        Value::Array(items) => items
+                            .iter()
+                            .map(|item| item.as_str().map(String::from).unwrap_or(item.to_string()))
+                            .collect::<Vec<_>>()
+                            .join(","),
+                        value => value.to_string(),
+                    };
+                    let param = Param {
+                        name,
+                        value,
+                        required: param["required"].as_bool().unwrap_or(false),
+                        description: param["description"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                    };
+                    match location {
+                        "path" => request.path_params.push((param.name, param.value)),
+                        "query" => request.query.push(param),
+                        "header" => request.headers.push(param),
+                        _ => {}
+                    }
+                }
+
+                let request_body = self.resolve(&opr.item["requestBody"]);
+                if let Some((content_type, media)) = self.response_media(request_body) {
+                    request.body = Some((content_type.to_string(), self.media_example(media)));
+                }
+
+                requests.push(request);
+            }
+        }
+        requests
+    }
+}
+
+impl Request<'_> {
+    /// The path with path params filled, eg: `/user/{id}` -> `/user/12`.
+    fn path(&self) -> String {
+        let mut path = self.opr.path.to_string();
+        for (name, value) in &self.path_params {
+            path = path.replace(&format!("{{{}}}", name), value);
+        }
+        path
+    }
+
+    fn body_text(&self) -> Option<(&str, String)> {
+        self.body.as_ref().map(|(content_type, body)| {
+            let text = match body {
+                Value::String(s) if !content_type.contains("json") => s.clone(),
+                body => serde_json::to_string_pretty(body).unwrap(),
+            };
+            (content_type.as_str(), text)
+        })
+    }
+}
+
+fn to_postman(builder: &OpenapiBuilder, requests: &[Request], base_url: &str) -> Value {
+    let mut folders: Vec<Value> = vec![];
+    for request in requests {
+        let opr = &request.opr;
+        let mut headers = request
+            .headers
+            .iter()
+            .map(|h| json!({ "key": h.name, "value": h.value, "description": h.description, "disabled": !h.required }))
+            .collect::<Vec<_>>();
+        let body = request.body_text().map(|(content_type, text)| {
+            headers.push(json!({ "key": "Content-Type", "value": content_type }));
+            json!({
+                "mode": "raw",
+                "raw": text,
+                "options": { "raw": { "language": if content_type.contains("json") { "json" } else { "text" } } }
+            })
+        });
+
+        let path = opr
+            .path
+            .trim_start_matches('/')
+            .split('/')
+            .map(
+                |segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                    Some(name) => format!(":{}", name),
+                    None => segment.to_string(),
+                },
+            )
+            .collect::<Vec<_>>();
+        let query = request
+            .query
+            .iter()
+            .map(|q| json!({ "key": q.name, "value": q.value, "description": q.description, "disabled": !q.required }))
+            .collect::<Vec<_>>();
+        let enabled = request
+            .query
+            .iter()
+            .filter(|q| q.required)
+            .map(|q| format!("{}={}", q.name, q.value))
+            .collect::<Vec<_>>();
+        let raw = format!(
+            "{{{{baseUrl}}}}/{}{}{}",
+            path.join("/"),
+            if enabled.is_empty() { "" } else { "?" },
+            enabled.join("&")
+        );
+
+        let mut item = json!({
+            "name": opr.router,
+            "request": {
+                "method": opr.method.to_uppercase(),
+                "description": request.description,
+                "header": headers,
+                "url": {
+                    "raw": raw,
+                    "host": ["{{baseUrl}}"],
+                    "path": path,
+                    "query": query,
+                    "variable": request.path_params.iter().map(|(key, value)| json!({ "key": key, "value": value })).collect::<Vec<_>>(),
+                },
+            },
+        });
+        if let Some(body) = body {
+            item["request"]["body"] = body;
+        }
+
+        match folders
+            .iter_mut()
+            .find(|folder| folder["name"] == opr.controller)
+        {
+            Some(folder) => folder["item"].as_array_mut().unwrap().push(item),
+            None => folders.push(json!({ "name": opr.controller, "item": [item] })),
+        }
+    }
+
+    json!({
+        "info": {
+            "name": builder.openapi["info"]["title"].as_str().unwrap_or("nidrs"),
+            "description": builder.openapi["info"]["description"].as_str().unwrap_or_default(),
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json",
+        },
+        "variable": [{ "key": "baseUrl", "value": base_url }],
+        "item": folders,
+    })
+}
+
+fn to_insomnia(builder: &OpenapiBuilder, requests: &[Request], base_url: &str) -> Value {
+    let workspace = "wrk_nidrs";
+    let mut resources = vec![
+        json!({
+            "_id": workspace,
+            "_type": "workspace",
+            "name": builder.openapi["info"]["title"].as_str().unwrap_or("nidrs"),
+            "description": builder.openapi["info"]["description"].as_str().unwrap_or_default(),
+        }),
+        json!({
+            "_id": "env_nidrs",
+            "_type": "environment",
+            "parentId": workspace,
+            "name": "Base Environment",
+            "data": { "baseUrl": base_url },
+        }),
+    ];
+
+    let mut folders = vec![];
+    for request in requests {
+        let opr = &request.opr;
+        let folder = format!("fld_{}", opr.controller);
+        if !folders.contains(&folder) {
+            resources.push(json!({
+                "_id": folder,
+                "_type": "request_group",
+                "parentId": workspace,
+                "name": opr.controller,
+            }));
+            folders.push(folder.clone());
+        }
+
+        let mut headers = request
+            .headers
+            .iter()
+            .map(|h| json!({ "name": h.name, "value": h.value, "disabled": !h.required }))
+            .collect::<Vec<_>>();
+        let body = match request.body_text() {
+            Some((content_type, text)) => {
+                headers.push(json!({ "name": "Content-Type", "value": content_type }));
+                json!({ "mimeType": content_type, "text": text })
+            }
+            None => json!({}),
+        };
+
+        resources.push(json!({
+            "_id": format!("req_{}_{}", opr.controller, opr.router),
+            "_type": "request",
+            "parentId": folder,
+            "name": opr.router,
+            "description": request.description,
+            "method": opr.method.to_uppercase(),
+            "url": format!("{{{{ _.baseUrl }}}}{}", request.path()),
+            "parameters": request.query.iter().map(|q| json!({ "name": q.name, "value": q.value, "description": q.description, "disabled": !q.required })).collect::<Vec<_>>(),
+            "headers": headers,
+            "body": body,
+        }));
+    }
+
+    json!({
+        "_type": "export",
+        "__export_format": 4,
+        "__export_source": "nidrs-cli",
+        "resources": resources,
+    })
+}
+
+fn to_http(requests: &[Request], base_url: &str) -> String {
+    let mut http = format!("@baseUrl = {}\n", base_url);
+    for request in requests {
+        let opr = &request.opr;
+        http.push_str(&format!("\n### {}.{}\n", opr.controller, opr.router));
+        for line in request.description.lines() {
+            http.push_str(format!("# {}", line).trim_end());
+            http.push('\n');
+        }
+
+        let query = request
+            .query
+            .iter()
+            .filter(|q| q.required)
+            .map(|q| format!("{}={}", q.name, q.value))
+            .collect::<Vec<_>>();
+        http.push_str(&format!(
+            "{} {{{{baseUrl}}}}{}{}{}\n",
+            opr.method.to_uppercase(),
+            request.path(),
+            if query.is_empty() { "" } else { "?" },
+            query.join("&")
+        ));
+        for header in request.headers.iter().filter(|h| h.required) {
+            http.push_str(&format!("{}: {}\n", header.name, header.value));
+        }
+        if let Some((content_type, text)) = request.body_text() {
+            http.push_str(&format!("Content-Type: {}\n\n{}\n", content_type, text));
+        }
+    }
+    http
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let mut builder = OpenapiBuilder {
+            openapi: json!({
+                "info": { "title": "demo" },
+                "servers": [{ "url": "https://{region}.example.com/v1/", "variables": { "region": { "default": "eu" } } }],
+                "paths": {
+                    "/user/{id}": {
+                        "put": {
+                            "x-controller": "UserController",
+                            "x-router": "update",
+                            "summary": "update a user",
+                            "parameters": [
+                                { "name": "id", "in": "path", "required": true, "example": 12, "schema": { "type": "integer" } },
+                                { "name": "page", "in": "query", "required": true, "schema": { "type": "integer", "example": 2 } }
+                            ],
+                            "requestBody": {
+                                "content": { "application/json": { "schema": { "type": "object" }, "example": { "name": "wuma" } } }
+                            }
+                        }
+                    }
+                }
+            }),
+        };
+        assert_eq!(
+            builder.base_url("./openapi.json"),
+            "https://eu.example.com/v1"
+        );
+
+        let requests = builder.requests();
+        let postman = to_postman(&builder, &requests, "http://localhost:3000");
+        let item = &postman["item"][0]["item"][0];
+        assert_eq!(postman["item"][0]["name"], "UserController");
+        assert_eq!(item["request"]["method"], "PUT");
+        assert_eq!(item["request"]["url"]["raw"], "{{baseUrl}}/user/:id?page=2");
+        assert_eq!(
+            item["request"]["url"]["variable"][0],
+            json!({ "key": "id", "value": "12" })
+        );
+
+        let insomnia = to_insomnia(&builder, &requests, "http://localhost:3000");
+        let request = &insomnia["resources"][3];
+        assert_eq!(
+            insomnia["resources"][1]["data"]["baseUrl"],
+            "http://localhost:3000"
+        );
+        assert_eq!(request["_id"], "req_UserController_update");
+        assert_eq!(request["url"], "{{ _.baseUrl }}/user/12");
+        assert_eq!(request["body"]["mimeType"], "application/json");
+
+        assert_eq!(
+            to_http(&requests, "http://localhost:3000"),
+            "@baseUrl = http://localhost:3000\n\n### UserController.update\n# update a user\nPUT {{baseUrl}}/user/12?page=2\nContent-Type: application/json\n\n{\n  \"name\": \"wuma\"\n}\n"
+        );
+
+        // a relative server is joined to the server the spec is read from
+        builder.openapi["servers"] = json!([{ "url": "/api/" }]);
+        assert_eq!(
+            builder.base_url("http://localhost:4000"),
+            "http://localhost:4000/api"
+        );
+        builder.openapi["servers"] = json!([{ "url": "/" }]);
+        assert_eq!(builder.base_url("./openapi.json"), "http://localhost:3000");
+    }
+}
@@ -0,0 +1,990 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+use syn::{
+    visit::{self, Visit},
+    Attribute, Expr, Fields, FnArg, GenericArgument, ImplItem, ImplItemFn, Item, LitStr,
+    PathArguments, ReturnType, Type,
+};
+
+use super::{OpenapiBuilder, HTTP_METHODS};
+
+/// eg: nid openapi extract ./ -o openapi.json
+#[derive(clap::Parser, Debug)]
+pub struct Extract {
+    /// the nidrs project or its `src` dir.
+    #[clap(default_value = ".")]
+    project: String,
+
+    /// where to write the spec, `-` prints it.
+    #[clap(short, long, default_value = "openapi.json")]
+    output: String,
+}
+
+impl Extract {
+    pub fn run(&self) {
+        let builder = match OpenapiBuilder::extract(Path::new(&self.project)) {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Openapi] {}", e);
+                std::process::exit(1);
+            }
+        };
+        let text = serde_json::to_string_pretty(&builder.openapi).unwrap();
+        if self.output == "-" {
+            println!("{}", text);
+            return;
+        }
+        if let Err(e) = std::fs::write(&self.output, text + "\n") {
+            eprintln!("[Openapi] write {}: {}", self.output, e);
+            std::process::exit(1);
+        }
+        println!(
+            "[Openapi] Extracted {} operations to {}",
+            builder.operations().len(),
+            self.output
+        );
+    }
+}
+
+impl OpenapiBuilder {
+    /// Build the spec from the sources of a nidrs project, without compiling or starting it.
+    pub fn extract(project: &Path) -> Result<Self, anyhow::Error> {
+        let src = if project.join("src").is_dir() {
+            project.join("src")
+        } else {
+            project.to_path_buf()
+        };
+        if !src.is_dir() {
+            return Err(anyhow::anyhow!("{:?} is not a nidrs project", project));
+        }
+
+        let mut paths = vec![];
+        rust_files(&src, &mut paths)?;
+        paths.sort();
+        let mut files = vec![];
+        for path in paths {
+            let text = std::fs::read_to_string(&path)?;
+            match syn::parse_file(&text) {
+                Ok(file) => files.push((
+                    path.strip_prefix(project).unwrap_or(&path).to_path_buf(),
+                    file,
+                )),
+                Err(e) => eprintln!("[Openapi] Skip {:?}: {}", path, e),
+            }
+        }
+
+        let mut openapi = extract(&files)?;
+        openapi["info"] = info(&src.parent().unwrap_or(&src).join("Cargo.toml"));
+        Ok(Self { openapi })
+    }
+}
+
+fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), anyhow::Error> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            rust_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The `info` of the spec from the package of `Cargo.toml`.
+fn info(cargo_toml: &Path) -> Value {
+    let package = std::fs::read_to_string(cargo_toml)
+        .ok()
+        .and_then(|text| text.parse::<toml::Table>().ok())
+        .and_then(|cargo| cargo.get("package").cloned())
+        .unwrap_or_else(|| toml::Value::Table(Default::default()));
+    let field = |key: &str| package.get(key).and_then(|v| v.as_str());
+    let mut info = json!({
+        "title": field("name").unwrap_or("nidrs"),
+        "version": field("version").unwrap_or("0.0.0"),
+    });
+    if let Some(description) = field("description") {
+        info["description"] = json!(description);
+    }
+    info
+}
+
+/// Build the `paths` and `components` of the spec from parsed sources.
+fn extract(files: &[(PathBuf, syn::File)]) -> Result<Value, anyhow::Error> {
+    let mut sources = Sources::default();
+    for (path, file) in files {
+        sources.module = vec![path.display().to_string()];
+        sources.visit_file(file);
+    }
+
+    let mut schemas = Schemas {
+        types: &sources.types,
+        locations: &sources.locations,
+        refs: BTreeSet::new(),
+        ambiguous: BTreeSet::new(),
+    };
+    let mut paths = BTreeMap::<String, Map<String, Value>>::new();
+    for (controller, func) in &sources.handlers {
+        let Some(base) = sources.controllers.get(controller) else {
+            continue;
+        };
+        let Some((method, route)) = route(&func.attrs) else {
+            continue;
+        };
+        let version = attr_str(&func.attrs, "version")
+            .or(base.version.clone())
+            .or(sources.version.clone());
+        let version = version.unwrap_or_default();
+        // nidrs apps put the version in the prefix, eg: `/api/{version}`
+        let prefix = match &sources.prefix {
+            Some(prefix) if prefix.contains("{version}") => prefix.replace("{version}", &version),
+            Some(prefix) => format!("{}/{}", prefix, version),
+            None => version,
+        };
+        let path = join_path(&[&prefix, &base.path, &route]);
+        let operation = schemas.operation(controller, func, &path);
+        if paths
+            .entry(path.clone())
+            .or_default()
+            .insert(method.clone(), operation)
+            .is_some()
+        {
+            eprintln!("[Openapi] Duplicate route {} {}", method, path);
+        }
+    }
+
+    // a type is looked up by its name, the one of several with the same name is not known
+    if !schemas.ambiguous.is_empty() {
+        let collisions = schemas
+            .ambiguous
+            .iter()
+            .map(|name| format!("`{}` in {}", name, sources.locations[name].join(", ")))
+            .collect::<Vec<_>>();
+        return Err(anyhow::anyhow!(
+            "types with the same name, rename all but one of them: {}",
+            collisions.join("; ")
+        ));
+    }
+
+    let mut components = Map::new();
+    let mut done = BTreeSet::new();
+    while let Some(name) = schemas.refs.difference(&done).next().cloned() {
+        done.insert(name.clone());
+        let item = sources.types[&name];
+        components.insert(name, schemas.component(item));
+    }
+
+    Ok(json!({
+        "openapi": "3.0.3",
+        "paths": paths,
+        "components": { "schemas": components },
+    }))
+}
+
+/// A `#[controller("/user")]` struct.
+struct Controller {
+    path: String,
+    version: Option<String>,
+}
+
+/// What the sources declare, collected across files before anything is resolved.
+#[derive(Default)]
+struct Sources<'a> {
+    controllers: BTreeMap<String, Controller>,
+    handlers: Vec<(String, &'a ImplItemFn)>,
+    /// structs and enums deriving serde traits
+    types: BTreeMap<String, &'a Item>,
+    /// where each of `types` is declared, eg: `src/user/dto.rs::inner`
+    locations: BTreeMap<String, Vec<String>>,
+    /// the file and inline modules being visited
+    module: Vec<String>,
+    /// `.default_prefix("/api")` of the app
+    prefix: Option<String>,
+    /// `.default_version("v1")` of the app
+    version: Option<String>,
+}
+
+impl<'a> Visit<'a> for Sources<'a> {
+    fn visit_item(&mut self, item: &'a Item) {
+        let (ident, attrs) = match item {
+            Item::Struct(item) => (&item.ident, &item.attrs),
+            Item::Enum(item) => (&item.ident, &item.attrs),
+            _ => return visit::visit_item(self, item),
+        };
+        if let Some(path) = attrs.iter().find(|attr| is_attr(attr, "controller")) {
+            self.controllers.insert(
+                ident.to_string(),
+                Controller {
+                    path: lit_arg(path).unwrap_or_default(),
+                    version: attr_str(attrs, "version"),
+                },
+            );
+        }
+        if is_serde(attrs) {
+            self.types.insert(ident.to_string(), item);
+            self.locations
+                .entry(ident.to_string())
+                .or_default()
+                .push(self.module.join("::"));
+        }
+        visit::visit_item(self, item);
+    }
+
+    fn visit_item_mod(&mut self, item: &'a syn::ItemMod) {
+        self.module.push(item.ident.to_string());
+        visit::visit_item_mod(self, item);
+        self.module.pop();
+    }
+
+    fn visit_item_impl(&mut self, item: &'a syn::ItemImpl) {
+        if let (None, Type::Path(ty)) = (&item.trait_, item.self_ty.as_ref()) {
+            if let Some(segment) = ty.path.segments.last() {
+                for impl_item in &item.items {
+                    if let ImplItem::Fn(func) = impl_item {
+                        self.handlers.push((segment.ident.to_string(), func));
+                    }
+                }
+            }
+        }
+        visit::visit_item_impl(self, item);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'a syn::ExprMethodCall) {
+        let value = call.args.first().and_then(|arg| match arg {
+            Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) => Some(lit.value()),
+            _ => None,
+        });
+        match call.method.to_string().as_str() {
+            "default_prefix" => self.prefix = value.or(self.prefix.take()),
+            "default_version" => self.version = value.or(self.version.take()),
+            _ => {}
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+}
+
+/// Schemas of the rust types, remembering the components they reference.
+struct Schemas<'a> {
+    types: &'a BTreeMap<String, &'a Item>,
+    locations: &'a BTreeMap<String, Vec<String>>,
+    refs: BTreeSet<String>,
+    /// the types used by name which are declared more than once
+    ambiguous: BTreeSet<String>,
+}
+
+impl<'a> Schemas<'a> {
+    fn operation(&mut self, controller: &str, func: &ImplItemFn, path: &str) -> Value {
+        let mut operation = json!({
+            "x-controller": controller,
+            "x-router": func.sig.ident.to_string(),
+            "tags": [controller],
+        });
+        if let Some(doc) = docs(&func.attrs) {
+            let (summary, description) = doc.split_once("\n\n").unwrap_or((&doc, ""));
+            operation["summary"] = json!(summary.replace('\n', " "));
+            if !description.is_empty() {
+                operation["description"] = json!(description);
+            }
+        }
+        if func.attrs.iter().any(|attr| is_attr(attr, "deprecated")) {
+            operation["deprecated"] = json!(true);
+        }
+
+        let mut parameters = vec![];
+        let path_names = super::params::path_params(path);
+        for input in &func.sig.inputs {
+            let FnArg::Typed(arg) = input else {
+                continue;
+            };
+            let Some((extractor, ty)) = generic(&arg.ty) else {
+                continue;
+            };
+            match extractor.as_str() {
+                "Json" | "Form" => {
+                    let content_type = if extractor == "Json" {
+                        "application/json"
+                    } else {
+                        "application/x-www-form-urlencoded"
+                    };
+                    operation["requestBody"] = json!({
+                        "required": true,
+                        "content": { content_type: { "schema": self.schema(ty) } },
+                    });
+                }
+                "Query" => parameters.extend(self.parameters(ty, "query")),
+                "Path" => match ty {
+                    Type::Tuple(tuple) => {
+                        for (name, ty) in path_names.iter().zip(&tuple.elems) {
+                            parameters.push(self.parameter(name, "path", ty, true, None));
+                        }
+                    }
+                    _ if self.fields(ty).is_some() => {
+                        parameters.extend(self.parameters(ty, "path"))
+                    }
+                    _ => {
+                        if let Some(name) = path_names.first() {
+                            parameters.push(self.parameter(name, "path", ty, true, None));
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+        // axum rejects a route whose path params are not all extracted, keep the spec complete anyway
+        for name in &path_names {
+            if !parameters
+                .iter()
+                .any(|p| p["in"] == "path" && p["name"] == *name)
+            {
+                parameters.push(json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                }));
+            }
+        }
+        if !parameters.is_empty() {
+            operation["parameters"] = json!(parameters);
+        }
+
+        let mut response = json!({ "description": "OK" });
+        if let ReturnType::Type(_, ty) = &func.sig.output {
+            if let Some((content_type, schema)) = self.response(ty) {
+                response["content"] = json!({ content_type: { "schema": schema } });
+            }
+        }
+        operation["responses"] = json!({ "200": response });
+        operation
+    }
+
+    /// The content of a handler's return type, `AppResult<Json<T>>` is json, `String` is text.
+    fn response(&mut self, ty: &Type) -> Option<(&'static str, Value)> {
+        match generic(ty) {
+            Some((wrapper, inner)) if ["AppResult", "Result"].contains(&wrapper.as_str()) => {
+                self.response(inner)
+            }
+            Some((wrapper, inner)) if wrapper == "Json" => {
+                Some(("application/json", self.schema(inner)))
+            }
+            Some((wrapper, _)) if wrapper == "Html" => {
+                Some(("text/html", json!({ "type": "string" })))
+            }
+            _ => match type_name(ty).as_deref() {
+                Some("String" | "str") => Some(("text/plain", json!({ "type": "string" }))),
+                _ => None,
+            },
+        }
+    }
+
+    /// The params of a `Query<T>` or `Path<T>` struct, one per field.
+    fn parameters(&mut self, ty: &Type, location: &str) -> Vec<Value> {
+        let Some((fields, rename_all)) = self.fields(ty) else {
+            return vec![];
+        };
+        let mut parameters = vec![];
+        for field in &fields.named {
+            let serde = Serde::parse(&field.attrs);
+            let Some(ident) = &field.ident else {
+                continue;
+            };
+            if serde.skip {
+                continue;
+            }
+            let name = serde
+                .rename
+                .unwrap_or_else(|| rename(&unraw(ident), rename_all.as_deref(), false));
+            let required = location == "path" || !(serde.default || is_optional(&field.ty));
+            parameters.push(self.parameter(
+                &name,
+                location,
+                &field.ty,
+                required,
+                docs(&field.attrs),
+            ));
+        }
+        parameters
+    }
+
+    fn parameter(
+        &mut self,
+        name: &str,
+        location: &str,
+        ty: &Type,
+        required: bool,
+        description: Option<String>,
+    ) -> Value {
+        let mut parameter = json!({
+            "name": name,
+            "in": location,
+            "required": required,
+            "schema": self.schema(ty),
+        });
+        if let Some(description) = description {
+            parameter["description"] = json!(description);
+        }
+        parameter
+    }
+
+    /// The serde type of a name, remembering the names declared more than once.
+    fn lookup(&mut self, name: &str) -> Option<&'a Item> {
+        let item = self.types.get(name)?;
+        if self.locations.get(name).is_some_and(|l| l.len() > 1) {
+            self.ambiguous.insert(name.to_string());
+        }
+        Some(item)
+    }
+
+    /// The named fields of a serde struct and its `rename_all`.
+    fn fields(&mut self, ty: &Type) -> Option<(&'a syn::FieldsNamed, Option<String>)> {
+        match self.lookup(&type_name(ty)?)? {
+            Item::Struct(item) => match &item.fields {
+                Fields::Named(fields) => Some((fields, Serde::parse(&item.attrs).rename_all)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The schema of a rust type, serde types are referenced as components.
+    fn schema(&mut self, ty: &Type) -> Value {
+        match ty {
+            Type::Reference(r) => return self.schema(&r.elem),
+            Type::Paren(p) => return self.schema(&p.elem),
+            Type::Group(g) => return self.schema(&g.elem),
+            Type::Array(a) => return json!({ "type": "array", "items": self.schema(&a.elem) }),
+            Type::Slice(s) => return json!({ "type": "array", "items": self.schema(&s.elem) }),
+            Type::Tuple(t) if t.elems.is_empty() => return json!({ "type": "null" }),
+            Type::Tuple(_) => return json!({ "type": "array" }),
+            _ => {}
+        }
+        let Some(name) = type_name(ty) else {
+            return json!({});
+        };
+        let args = type_args(ty);
+        match name.as_str() {
+            "String" | "str" | "char" => json!({ "type": "string" }),
+            "bool" => json!({ "type": "boolean" }),
+            "i8" | "i16" | "i32" | "u8" | "u16" | "u32" => {
+                json!({ "type": "integer", "format": "int32" })
+            }
+            "i64" | "i128" | "isize" | "u64" | "u128" | "usize" => {
+                json!({ "type": "integer", "format": "int64" })
+            }
+            "f32" => json!({ "type": "number", "format": "float" }),
+            "f64" => json!({ "type": "number", "format": "double" }),
+            "DateTime" | "NaiveDateTime" | "OffsetDateTime" | "SystemTime" => {
+                json!({ "type": "string", "format": "date-time" })
+            }
+            "NaiveDate" | "Date" => json!({ "type": "string", "format": "date" }),
+            "Uuid" => json!({ "type": "string", "format": "uuid" }),
+            "Vec" | "VecDeque" | "HashSet" | "BTreeSet" | "LinkedList" => {
+                let items = args.first().map(|ty| self.schema(ty)).unwrap_or_default();
+                json!({ "type": "array", "items": items })
+            }
+            "HashMap" | "BTreeMap" | "IndexMap" => {
+                let values = args.get(1).map(|ty| self.schema(ty)).unwrap_or_default();
+                json!({ "type": "object", "additionalProperties": values })
+            }
+            "Option" | "Box" | "Rc" | "Arc" | "Cow" => match args.last() {
+                Some(ty) => self.schema(ty),
+                None => json!({}),
+            },
+            name if self.lookup(name).is_some() => {
+                self.refs.insert(name.to_string());
+                json!({ "$ref": format!("#/components/schemas/{}", name) })
+            }
+            _ => json!({}),
+        }
+    }
+
+    /// The component schema of a serde struct or enum.
+    fn component(&mut self, item: &Item) -> Value {
+        let (attrs, mut schema) = match item {
+            Item::Struct(item) => {
+                let serde = Serde::parse(&item.attrs);
+                let schema = match &item.fields {
+                    Fields::Named(fields) => {
+                        self.object(&fields.named, serde.rename_all, serde.default)
+                    }
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                        self.schema(&fields.unnamed[0].ty)
+                    }
+                    Fields::Unnamed(_) => json!({ "type": "array" }),
+                    Fields::Unit => json!({ "type": "null" }),
+                };
+                (&item.attrs, schema)
+            }
+            Item::Enum(item) => (&item.attrs, self.enumeration(item)),
+            _ => return json!({}),
+        };
+        if let Some(doc) = docs(attrs) {
+            schema["description"] = json!(doc);
+        }
+        schema
+    }
+
+    fn object<'f>(
+        &mut self,
+        fields: impl IntoIterator<Item = &'f syn::Field>,
+        rename_all: Option<String>,
+        container_default: bool,
+    ) -> Value {
+        let mut properties = Map::new();
+        let mut required = vec![];
+        let mut flatten = vec![];
+        for field in fields {
+            let serde = Serde::parse(&field.attrs);
+            let Some(ident) = &field.ident else {
+                continue;
+            };
+            if serde.skip {
+                continue;
+            }
+            if serde.flatten {
+                flatten.push(self.schema(&field.ty));
+                continue;
+            }
+            let name = serde
+                .rename
+                .unwrap_or_else(|| rename(&unraw(ident), rename_all.as_deref(), false));
+            let mut schema = self.schema(&field.ty);
+            if let Some(doc) = docs(&field.attrs) {
+                schema = if schema.get("$ref").is_some() {
+                    json!({ "allOf": [schema], "description": doc })
+                } else {
+                    schema["description"] = json!(doc);
+                    schema
+                };
+            }
+            if !(serde.default || serde.optional || container_default || is_optional(&field.ty)) {
+                required.push(name.clone());
+            }
+            properties.insert(name, schema);
+        }
+
+        let mut object = json!({ "type": "object", "properties": properties });
+        if !required.is_empty() {
+            object["required"] = json!(required);
+        }
+        if flatten.is_empty() {
+            object
+        } else {
+            flatten.push(object);
+            json!({ "allOf": flatten })
+        }
+    }
+
+    /// Unit variants are a string enum, other variants follow the serde tagging of the enum.
+    fn enumeration(&mut self, item: &syn::ItemEnum) -> Value {
+        let serde = Serde::parse(&item.attrs);
+        let name = |variant: &syn::Variant| {
+            Serde::parse(&variant.attrs).rename.unwrap_or_else(|| {
+                rename(
+                    &variant.ident.to_string(),
+                    serde.rename_all.as_deref(),
+                    true,
+                )
+            })
+        };
+        if item
+            .variants
+            .iter()
+            .all(|v| matches!(v.fields, Fields::Unit))
+            && serde.tag.is_none()
+        {
+            let values = item.variants.iter().map(name).collect::<Vec<_>>();
+            return json!({ "type": "string", "enum": values });
+        }
+
+        let mut one_of = vec![];
+        for variant in &item.variants {
+            let tag = name(variant);
+            let inner = match &variant.fields {
+                Fields::Unit => None,
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    Some(self.schema(&fields.unnamed[0].ty))
+                }
+                Fields::Unnamed(_) => Some(json!({ "type": "array" })),
+                Fields::Named(fields) => Some(self.object(&fields.named, None, false)),
+            };
+            one_of.push(match (&serde.tag, inner) {
+                (_, None) if serde.untagged => json!({ "type": "null" }),
+                (_, Some(inner)) if serde.untagged => inner,
+                (Some(key), inner) => {
+                    let tag = json!({
+                        "type": "object",
+                        "properties": { key.as_str(): { "type": "string", "enum": [tag] } },
+                        "required": [key],
+                    });
+                    match inner {
+                        Some(inner) => json!({ "allOf": [tag, inner] }),
+                        None => tag,
+                    }
+                }
+                (None, None) => json!({ "type": "string", "enum": [tag] }),
+                (None, Some(inner)) => json!({
+                    "type": "object",
+                    "properties": { tag.as_str(): inner },
+                    "required": [tag],
+                }),
+            });
+        }
+        json!({ "oneOf": one_of })
+    }
+}
+
+/// The serde attributes the schema depends on.
+#[derive(Default)]
+struct Serde {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    skip: bool,
+    default: bool,
+    /// `skip_serializing_if`
+    optional: bool,
+    flatten: bool,
+    tag: Option<String>,
+    untagged: bool,
+}
+
+impl Serde {
+    fn parse(attrs: &[Attribute]) -> Self {
+        let mut serde = Serde::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            let _ = attr.parse_nested_meta(|meta| {
+                let key = meta
+                    .path
+                    .get_ident()
+                    .map(|i| i.to_string())
+                    .unwrap_or_default();
+                match key.as_str() {
+                    "rename" | "rename_all" => {
+                        let mut value = None;
+                        if meta.input.peek(syn::Token![=]) {
+                            value = Some(meta.value()?.parse::<LitStr>()?.value());
+                        } else {
+                            // rename(serialize = "..", deserialize = "..")
+                            meta.parse_nested_meta(|inner| {
+                                let lit = inner.value()?.parse::<LitStr>()?;
+                                if inner.path.is_ident("serialize") {
+                                    value = Some(lit.value());
+                                }
+                                Ok(())
+                            })?;
+                        }
+                        if key == "rename" {
+                            serde.rename = value;
+                        } else {
+                            serde.rename_all = value;
+                        }
+                    }
+                    "skip" => serde.skip = true,
+                    "flatten" => serde.flatten = true,
+                    "untagged" => serde.untagged = true,
+                    "tag" => serde.tag = Some(meta.value()?.parse::<LitStr>()?.value()),
+                    _ => {
+                        serde.default |= key == "default";
+                        serde.optional |= key == "skip_serializing_if";
+                        skip_value(&meta)?;
+                    }
+                }
+                Ok(())
+            });
+        }
+        serde
+    }
+}
+
+/// Consume the `= ..` or `(..)` of a serde attribute we don't read.
+fn skip_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.step(|cursor| {
+            let mut rest = *cursor;
+            while let Some((_, next)) = rest.token_tree() {
+                rest = next;
+            }
+            Ok(((), rest))
+        })?;
+    }
+    Ok(())
+}
+
+/// Apply a serde `rename_all` rule to a snake_case field or a PascalCase variant.
+fn rename(name: &str, rule: Option<&str>, variant: bool) -> String {
+    let Some(rule) = rule else {
+        return name.to_string();
+    };
+    let mut words: Vec<String> = vec![];
+    for part in name.split('_').filter(|p| !p.is_empty()) {
+        let mut word = String::new();
+        for c in part.chars() {
+            if c.is_ascii_uppercase() && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        words.push(word);
+    }
+    let lower = words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>();
+    let upper = words.iter().map(|w| w.to_uppercase()).collect::<Vec<_>>();
+    let capital = lower
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    match rule {
+        "lowercase" if variant => lower.concat(),
+        "lowercase" => lower.join("_"),
+        "UPPERCASE" if variant => upper.concat(),
+        "UPPERCASE" => upper.join("_"),
+        "PascalCase" => capital.concat(),
+        "camelCase" => lower.first().cloned().unwrap_or_default() + &capital[1..].concat(),
+        "snake_case" => lower.join("_"),
+        "SCREAMING_SNAKE_CASE" => upper.join("_"),
+        "kebab-case" => lower.join("-"),
+        "SCREAMING-KEBAB-CASE" => upper.join("-"),
+        _ => name.to_string(),
+    }
+}
+
+/// The method and path of a route attribute, eg: `#[get("/:id")]`.
+fn route(attrs: &[Attribute]) -> Option<(String, String)> {
+    attrs.iter().find_map(|attr| {
+        let method = attr.path().segments.last()?.ident.to_string();
+        HTTP_METHODS
+            .contains(&method.as_str())
+            .then(|| (method, lit_arg(attr).unwrap_or_default()))
+    })
+}
+
+/// Join path parts, axum's `:id` and `*rest` become `{id}` and `{rest}`.
+fn join_path(parts: &[&str]) -> String {
+    let segments = parts
+        .iter()
+        .flat_map(|part| part.split('/'))
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| match segment.strip_prefix([':', '*']) {
+            Some(name) => format!("{{{}}}", name),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>();
+    format!("/{}", segments.join("/"))
+}
+
+fn is_attr(attr: &Attribute, name: &str) -> bool {
+    attr.path().segments.last().is_some_and(|s| s.ident == name)
+}
+
+/// The string argument of an attribute, eg: `#[version("v2")]`.
+fn lit_arg(attr: &Attribute) -> Option<String> {
+    attr.parse_args::<LitStr>().ok().map(|lit| lit.value())
+}
+
+fn attr_str(attrs: &[Attribute], name: &str) -> Option<String> {
+    attrs
+        .iter()
+        .find(|attr| is_attr(attr, name))
+        .and_then(lit_arg)
+}
+
+/// Whether the item derives `Serialize` or `Deserialize`.
+fn is_serde(attrs: &[Attribute]) -> bool {
+    let mut serde = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            serde |= meta
+                .path
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "Serialize" || s.ident == "Deserialize");
+            Ok(())
+        });
+    }
+    serde
+}
+
+/// The doc comment of an item, `None` when it has none.
+fn docs(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit),
+                        ..
+                    }),
+                ..
+            }) => Some(lit.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).to_string())
+        .collect::<Vec<_>>();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+fn unraw(ident: &syn::Ident) -> String {
+    let name = ident.to_string();
+    name.strip_prefix("r#").unwrap_or(&name).to_string()
+}
+
+/// The last segment of a type path, eg: `axum::Json<T>` -> `Json`.
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(ty) => ty.path.segments.last().map(|s| s.ident.to_string()),
+        Type::Reference(r) => type_name(&r.elem),
+        _ => None,
+    }
+}
+
+fn type_args(ty: &Type) -> Vec<&Type> {
+    let Type::Path(ty) = ty else {
+        return vec![];
+    };
+    let Some(PathArguments::AngleBracketed(args)) = ty.path.segments.last().map(|s| &s.arguments)
+    else {
+        return vec![];
+    };
+    args.args
+        .iter()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A wrapper type and its first argument, eg: `Json<UserDto>` -> (`Json`, `UserDto`).
+fn generic(ty: &Type) -> Option<(String, &Type)> {
+    Some((type_name(ty)?, type_args(ty).into_iter().next()?))
+}
+
+fn is_optional(ty: &Type) -> bool {
+    type_name(ty).as_deref() == Some("Option")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let source = r#"
+            #[controller("/user")]
+            pub struct UserController {
+                user_service: Inject<UserService>,
+            }
+
+            impl UserController {
+                /// get a user
+                #[get("/:id")]
+                pub async fn get_one(&self, Path(id): Path<i32>, Query(q): Query<FilterDto>) -> AppResult<Json<UserDto>> {
+                    todo!()
+                }
+
+                #[post("/")]
+                pub async fn create(&self, Json(dto): Json<CreateUserDto>) -> AppResult<String> {
+                    todo!()
+                }
+            }
+
+            #[derive(Deserialize)]
+            pub struct FilterDto {
+                pub page: Option<u32>,
+            }
+
+            #[derive(Serialize, Deserialize)]
+            #[serde(rename_all = "camelCase")]
+            pub struct CreateUserDto {
+                /// user name
+                pub user_name: String,
+                #[serde(default)]
+                pub role: Role,
+            }
+
+            #[derive(Serialize)]
+            pub struct UserDto {
+                pub id: i64,
+                #[serde(flatten)]
+                pub user: CreateUserDto,
+            }
+
+            #[derive(Serialize, Deserialize)]
+            #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+            pub enum Role {
+                SuperAdmin,
+                Guest,
+            }
+
+            fn main() {
+                App::new().default_prefix("/api").listen(3000);
+            }
+        "#;
+        let openapi = extract(&[(
+            PathBuf::from("src/main.rs"),
+            syn::parse_file(source).unwrap(),
+        )])
+        .unwrap();
+
+        let get_one = &openapi["paths"]["/api/user/{id}"]["get"];
+        assert_eq!(get_one["x-router"], "get_one");
+        assert_eq!(get_one["summary"], "get a user");
+        assert_eq!(get_one["parameters"][0]["name"], "id");
+        assert_eq!(get_one["parameters"][1]["name"], "page");
+        assert_eq!(get_one["parameters"][1]["required"], false);
+        assert_eq!(
+            get_one["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/UserDto"
+        );
+
+        let create = &openapi["paths"]["/api/user"]["post"];
+        assert!(create["responses"]["200"]["content"]["text/plain"].is_object());
+
+        let schemas = &openapi["components"]["schemas"];
+        assert_eq!(
+            schemas["CreateUserDto"]["properties"]["userName"]["description"],
+            "user name"
+        );
+        assert_eq!(schemas["CreateUserDto"]["required"], json!(["userName"]));
+        assert_eq!(schemas["Role"]["enum"], json!(["SUPER_ADMIN", "GUEST"]));
+        assert!(schemas["UserDto"]["allOf"].is_array());
+        assert!(schemas.get("FilterDto").is_none());
+
+        // a `UserDto` in another module is ambiguous once it is used
+        let other = syn::parse_file(
+            "mod admin { #[derive(Serialize)] pub struct UserDto { pub role: String } }",
+        )
+        .unwrap();
+        let files = [
+            (
+                PathBuf::from("src/main.rs"),
+                syn::parse_file(source).unwrap(),
+            ),
+            (PathBuf::from("src/admin.rs"), other),
+        ];
+        let error = extract(&files).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "types with the same name, rename all but one of them: `UserDto` in src/main.rs, src/admin.rs::admin"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use super::{api_key, api_path, naming::ts_member, to_pascal_case, OpenapiBuilder};
+
+/// The data fetching library the hooks are generated for.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hooks {
+    /// @tanstack/react-query
+    ReactQuery,
+    /// swr
+    Swr,
+}
+
+impl OpenapiBuilder {
+    /// Typed hooks on top of the generated `Api` class, GET becomes a query and other methods become mutations.
+    ///
+    /// Query keys are `[controller, router, dto]`, so `invalidate.user()` refreshes every query of `UserController`.
+    /// Mutation keys are `[controller, router]`, one per operation.
+    ///
+    /// Streaming operations are left out, iterate them on `api` directly.
+    pub fn to_hooks(&self, hooks: Hooks) -> String {
+        let controllers = self.controllers();
+
+        let mut ts = "// @ts-nocheck eslint-disable prettier-ignore\n".to_string();
+        ts.push_str(match hooks {
+            Hooks::ReactQuery => "import { useQuery, useMutation, useQueryClient, type QueryClient, type UseQueryOptions, type UseMutationOptions } from \"@tanstack/react-query\";\n",
+            Hooks::Swr => "import useSWR, { mutate as globalMutate, type SWRConfiguration } from \"swr\";\nimport useSWRMutation, { type SWRMutationConfiguration } from \"swr/mutation\";\n",
+        });
+        ts.push_str("import type { Api } from \"./index\";\n\n");
+        ts.push_str("type Dto<F> = F extends (dto?: infer D) => any ? D : never;\n");
+        ts.push_str(
+            "type Res<F> = F extends (...args: any[]) => Promise<infer R> ? R : never;\n\n",
+        );
+
+        ts.push_str("/* prettier-ignore */\nexport const queryKeys = {\n");
+        for (controller, operations) in &controllers {
+            let key = api_key(controller);
+            let api = api_type(controller);
+            ts.push_str(&format!("  {key}: {{\n    all: [\"{key}\"] as const,\n"));
+            for opr in operations
+                .iter()
+                .filter(|opr| self.stream_media(opr.item).is_none())
+            {
+                let router = ts_member(opr.router);
+                ts.push_str(&if is_query(opr.method) {
+                    format!("    {router}: (dto?: Dto<{api}[\"{router}\"]>) => [\"{key}\", \"{router}\", dto ?? {{}}] as const,\n")
+                } else {
+                    format!("    {router}: [\"{key}\", \"{router}\"] as const,\n")
+                });
+            }
+            ts.push_str("  },\n");
+        }
+        ts.push_str("};\n\n");
+
+        ts.push_str("/* prettier-ignore */\nexport const invalidate = {\n");
+        for controller in controllers.keys() {
+            let key = api_key(controller);
+            ts.push_str(&match hooks {
+                Hooks::ReactQuery => format!(
+                    "  {key}: (queryClient: QueryClient) => queryClient.invalidateQueries({{ queryKey: queryKeys.{key}.all }}),\n"
+                ),
+                Hooks::Swr => format!(
+                    "  {key}: (mutate = globalMutate) => mutate((key) => Array.isArray(key) && key[0] === \"{key}\"),\n"
+                ),
+            });
+        }
+        ts.push_str("};\n\n");
+
+        ts.push_str("/* prettier-ignore */\nexport function createHooks(api: Api) {\n  return {\n");
+        for (controller, operations) in &controllers {
+            let key = api_key(controller);
+            let path = api_path(controller);
+            let api = api_type(controller);
+            ts.push_str(&format!("    {key}: {{\n"));
+            for opr in operations
+                .iter()
+                .filter(|opr| self.stream_media(opr.item).is_none())
+            {
+                let router = ts_member(opr.router);
+                let name = ts_member(&format!("use{}", to_pascal_case(opr.router)));
+                let f = format!("{api}[\"{router}\"]");
+                let hook = match (hooks, is_query(opr.method)) {
+                    (Hooks::ReactQuery, true) => format!(
+                        "{name}(dto?: Dto<{f}>, options?: Omit<UseQueryOptions<Res<{f}>>, \"queryKey\" | \"queryFn\">) {{\n        return useQuery({{ queryKey: queryKeys.{key}.{router}(dto), queryFn: () => api.{path}.{router}(dto), ...options }});\n      }}"
+                    ),
+                    (Hooks::ReactQuery, false) => format!(
+                        "{name}(options?: UseMutationOptions<Res<{f}>, unknown, Dto<{f}>>) {{\n        const queryClient = useQueryClient();\n        return useMutation({{\n          mutationKey: queryKeys.{key}.{router},\n          mutationFn: (dto: Dto<{f}>) => api.{path}.{router}(dto),\n          ...options,\n          onSuccess: (...args) => {{\n            invalidate.{key}(queryClient);\n            return options?.onSuccess?.(...args);\n          }},\n        }});\n      }}"
+                    ),
+                    (Hooks::Swr, true) => format!(
+                        "{name}(dto?: Dto<{f}> | null, config?: SWRConfiguration<Res<{f}>>) {{\n        return useSWR(dto === null ? null : queryKeys.{key}.{router}(dto), () => api.{path}.{router}(dto ?? undefined), config);\n      }}"
+                    ),
+                    (Hooks::Swr, false) => format!(
+                        "{name}(config?: SWRMutationConfiguration<Res<{f}>, unknown, readonly unknown[], Dto<{f}>>) {{\n        return useSWRMutation(queryKeys.{key}.{router}, (_key, {{ arg }}: {{ arg: Dto<{f}> }}) => api.{path}.{router}(arg), {{\n          ...config,\n          onSuccess: (...args) => {{\n            invalidate.{key}();\n            return config?.onSuccess?.(...args);\n          }},\n        }});\n      }}"
+                    ),
+                };
+                ts.push_str(&format!("      {},\n", hook));
+            }
+            ts.push_str("    },\n");
+        }
+        ts.push_str("  };\n}\n");
+
+        ts
+    }
+}
+
+/// The type of a controller on `Api`, eg: `billing.InvoiceController` -> `Api["billing"]["invoice"]`.
+fn api_type(controller: &str) -> String {
+    api_path(controller)
+        .split('.')
+        .fold("Api".to_string(), |ty, key| format!("{}[\"{}\"]", ty, key))
+}
+
+fn is_query(method: &str) -> bool {
+    method == "get" || method == "head"
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_hooks() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/user/{id}": {
+                        "get": { "x-controller": "UserController", "x-router": "get_one" },
+                        "put": { "x-controller": "UserController", "x-router": "update" }
+                    }
+                }
+            }),
+        };
+        insta::assert_snapshot!("hooks_react_query", builder.to_hooks(Hooks::ReactQuery));
+        insta::assert_snapshot!("hooks_swr", builder.to_hooks(Hooks::Swr));
+    }
+}
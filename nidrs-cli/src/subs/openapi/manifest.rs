@@ -0,0 +1,166 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// The manifest of `out_dir`, records the files `nid openapi` owns and their hashes.
+pub const MANIFEST_FILE: &str = ".nid-manifest.json";
+
+pub struct Manifest {
+    pub files: BTreeMap<String, String>,
+}
+
+/// What a regeneration changes in `out_dir`.
+#[derive(Default)]
+pub struct Sync {
+    pub write: Vec<PathBuf>,
+    pub remove: Vec<PathBuf>,
+    /// Files changed by hand since the last generation, or not generated by nid at all.
+    pub edited: Vec<PathBuf>,
+}
+
+impl Manifest {
+    pub fn read(out_dir: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(out_dir.join(MANIFEST_FILE)).ok()?;
+        let manifest: Value = serde_json::from_str(&text).ok()?;
+        let files = manifest["files"]
+            .as_object()?
+            .iter()
+            .filter_map(|(file, hash)| Some((file.clone(), hash.as_str()?.to_string())))
+            .collect();
+        Some(Self { files })
+    }
+
+    /// The manifest of the generated files.
+    pub fn new(files: &[(PathBuf, String)]) -> Self {
+        Self {
+            files: files
+                .iter()
+                .map(|(file, content)| (manifest_key(file), hash(content.as_bytes())))
+                .collect(),
+        }
+    }
+
+    /// Take over the generated files of a directory generated before the manifest existed.
+    pub fn adopt(out_dir: &Path, files: &[(PathBuf, String)]) -> Self {
+        Self {
+            files: files
+                .iter()
+                .filter_map(|(file, _)| {
+                    let content = std::fs::read(out_dir.join(file)).ok()?;
+                    Some((manifest_key(file), hash(&content)))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn write(&self, out_dir: &Path) -> std::io::Result<()> {
+        let manifest = json!({
+            "generator": format!("nidrs-cli {}", env!("CARGO_PKG_VERSION")),
+            "files": self.files,
+        });
+        std::fs::write(
+            out_dir.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(&manifest).unwrap() + "\n",
+        )
+    }
+
+    /// Compare the generated files with `out_dir`, unchanged files are left alone.
+    ///
+    /// Owned files are rewritten or removed only when they still match their hash, `force` overrides hand edits.
+    pub fn sync(&self, out_dir: &Path, files: &[(PathBuf, String)], force: bool) -> Sync {
+        let mut sync = Sync::default();
+        for (file, content) in files {
+            let Ok(current) = std::fs::read(out_dir.join(file)) else {
+                sync.write.push(file.clone());
+                continue;
+            };
+            let current = hash(&current);
+            if current == hash(content.as_bytes()) {
+                continue;
+            }
+            if self.files.get(&manifest_key(file)) != Some(&current) {
+                sync.edited.push(file.clone());
+                if !force {
+                    continue;
+                }
+            }
+            sync.write.push(file.clone());
+        }
+
+        for (file, owned) in &self.files {
+            let file = PathBuf::from(file);
+            if files.iter().any(|(generated, _)| *generated == file) {
+                continue;
+            }
+            let Ok(current) = std::fs::read(out_dir.join(&file)) else {
+                continue;
+            };
+            if hash(&current) != *owned {
+                sync.edited.push(file.clone());
+                if !force {
+                    continue;
+                }
+            }
+            sync.remove.push(file);
+        }
+        sync
+    }
+}
+
+fn manifest_key(file: &Path) -> String {
+    file.to_string_lossy().replace('\\', "/")
+}
+
+fn hash(content: &[u8]) -> String {
+    let digest = Sha256::digest(content);
+    let hex = digest
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    format!("sha256:{}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync() {
+        let out_dir = std::env::temp_dir().join(format!("nid-manifest-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&out_dir);
+        std::fs::create_dir_all(&out_dir).unwrap();
+
+        let v1 = vec![
+            (PathBuf::from("index.ts"), "v1".to_string()),
+            (PathBuf::from("msw.ts"), "v1".to_string()),
+            (PathBuf::from("hooks.ts"), "v1".to_string()),
+        ];
+        for (file, content) in &v1 {
+            std::fs::write(out_dir.join(file), content).unwrap();
+        }
+        Manifest::new(&v1).write(&out_dir).unwrap();
+        std::fs::write(out_dir.join("msw.ts"), "edited").unwrap();
+
+        let v2 = vec![
+            (PathBuf::from("index.ts"), "v2".to_string()),
+            (PathBuf::from("msw.ts"), "v2".to_string()),
+        ];
+        let manifest = Manifest::read(&out_dir).unwrap();
+        let sync = manifest.sync(&out_dir, &v2, false);
+        assert_eq!(sync.write, vec![PathBuf::from("index.ts")]);
+        assert_eq!(sync.remove, vec![PathBuf::from("hooks.ts")]);
+        assert_eq!(sync.edited, vec![PathBuf::from("msw.ts")]);
+
+        let sync = manifest.sync(&out_dir, &v2, true);
+        assert_eq!(
+            sync.write,
+            vec![PathBuf::from("index.ts"), PathBuf::from("msw.ts")]
+        );
+
+        let _ = std::fs::remove_dir_all(&out_dir);
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use super::{OpenapiBuilder, HTTP_METHODS};
+
+/// A service spec to merge, eg: `billing=http://localhost:3001` with `--base-url billing=/billing`.
+pub struct Service {
+    pub namespace: String,
+    pub builder: OpenapiBuilder,
+    pub base_url: Option<String>,
+}
+
+impl OpenapiBuilder {
+    /// Merge the specs of several services into one, namespacing their controllers as `billing.InvoiceController`.
+    ///
+    /// The base url of a service prefixes its paths. Components with the same name but a different
+    /// definition, and operations on the same method and path, are reported as conflicts.
+    pub fn merge(services: &[Service]) -> Result<Self, anyhow::Error> {
+        let mut paths = Map::new();
+        let mut components = Map::new();
+        let mut tags = vec![];
+        let mut owners = HashMap::new();
+        let mut conflicts = vec![];
+
+        for service in services {
+            let openapi = &service.builder.openapi;
+            let base_url = service
+                .base_url
+                .as_deref()
+                .unwrap_or_default()
+                .trim_end_matches('/');
+
+            for (path, item) in openapi["paths"].as_object().into_iter().flatten() {
+                let key = format!("{}{}", base_url, path);
+                let Value::Object(merged) = paths.entry(key.clone()).or_insert(json!({})) else {
+                    continue;
+                };
+                for (method, opr) in item.as_object().into_iter().flatten() {
+                    if !HTTP_METHODS.contains(&method.as_str()) {
+                        continue;
+                    }
+                    let owner = format!("{} {}", method.to_uppercase(), key);
+                    if let Some(other) = owners.get(&owner) {
+                        conflicts.push(format!("{}: {}, {}", owner, other, service.namespace));
+                        continue;
+                    }
+                    owners.insert(owner, &service.namespace);
+
+                    let mut opr = opr.clone();
+                    // path level parameters move down, the merged path item may belong to several services
+                    let mut parameters = item["parameters"].as_array().cloned().unwrap_or_default();
+                    parameters.retain(|param| {
+                        !opr["parameters"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .any(|p| p["name"] == param["name"] && p["in"] == param["in"])
+                    });
+                    parameters.extend(opr["parameters"].as_array().cloned().unwrap_or_default());
+                    if !parameters.is_empty() {
+                        opr["parameters"] = Value::Array(parameters);
+                    }
+                    if let Some(controller) = opr["x-controller"].as_str() {
+                        opr["x-controller"] =
+                            json!(format!("{}.{}", service.namespace, controller));
+                    }
+                    opr["x-service"] = json!(service.namespace);
+                    merged.insert(method.to_string(), opr);
+                }
+            }
+
+            for (kind, items) in openapi["components"].as_object().into_iter().flatten() {
+                let Value::Object(merged) = components.entry(kind.clone()).or_insert(json!({}))
+                else {
+                    continue;
+                };
+                for (name, item) in items.as_object().into_iter().flatten() {
+                    let owner = format!("{}/{}", kind, name);
+                    match merged.get(name) {
+                        Some(other) if other != item => conflicts.push(format!(
+                            "{} `{}`: {}, {}",
+                            kind, name, owners[&owner], service.namespace
+                        )),
+                        Some(_) => {}
+                        None => {
+                            merged.insert(name.clone(), item.clone());
+                            owners.insert(owner, &service.namespace);
+                        }
+                    }
+                }
+            }
+
+            for tag in openapi["tags"].as_array().into_iter().flatten() {
+                if !tags.iter().any(|t: &Value| t["name"] == tag["name"]) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+
+        if !conflicts.is_empty() {
+            return Err(anyhow::anyhow!(
+                "conflicts between services, rename them or give the services distinct `--base-url`s:\n  {}",
+                conflicts.join("\n  ")
+            ));
+        }
+
+        let first = services
+            .first()
+            .map(|service| &service.builder.openapi)
+            .unwrap_or(&Value::Null);
+        let mut openapi = json!({
+            "openapi": first["openapi"].as_str().unwrap_or("3.0.0"),
+            "info": {
+                "title": services.iter().map(|s| s.namespace.as_str()).collect::<Vec<_>>().join(", "),
+                "version": first["info"]["version"].as_str().unwrap_or("0.0.0"),
+            },
+            "paths": paths,
+            "components": components,
+        });
+        if !tags.is_empty() {
+            openapi["tags"] = Value::Array(tags);
+        }
+        Ok(Self { openapi })
+    }
+}
+
+/// Parse a `key=value` argument, eg: `billing=http://localhost:3001`.
+pub fn parse_key_value(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected `key=value`, got `{}`", arg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(namespace: &str, base_url: Option<&str>, schema: Value) -> Service {
+        Service {
+            namespace: namespace.to_string(),
+            base_url: base_url.map(String::from),
+            builder: OpenapiBuilder {
+                openapi: json!({
+                    "paths": {
+                        "/health": { "get": { "x-controller": "AppController", "x-router": "health" } }
+                    },
+                    "components": { "schemas": { "ErrorDto": schema } }
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let error = json!({ "type": "object" });
+        let builder = OpenapiBuilder::merge(&[
+            service("billing", Some("/billing/"), error.clone()),
+            service("user", Some("https://user.example.com"), error.clone()),
+        ])
+        .unwrap();
+        let opr = &builder.openapi["paths"]["/billing/health"]["get"];
+        assert_eq!(opr["x-controller"], "billing.AppController");
+        assert_eq!(opr["x-service"], "billing");
+        assert!(builder.openapi["paths"]["https://user.example.com/health"].is_object());
+        assert_eq!(builder.openapi["components"]["schemas"]["ErrorDto"], error);
+
+        let err = OpenapiBuilder::merge(&[
+            service("billing", None, error),
+            service("user", None, json!({ "type": "string" })),
+        ])
+        .err()
+        .unwrap()
+        .to_string();
+        assert!(err.contains("GET /health: billing, user"));
+        assert!(err.contains("schemas `ErrorDto`: billing, user"));
+    }
+}
@@ -0,0 +1,351 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde_json::{json, Value};
+
+use super::{OpenapiBuilder, Operation};
+
+/// eg: nid openapi mock ./openapi.json --port 4000 --fixtures ./mocks
+#[derive(clap::Parser, Debug)]
+pub struct Mock {
+    /// nidrs server address or openapi spec file.
+    #[clap(default_value = "http://localhost:3000")]
+    spec: String,
+
+    #[clap(short, long, default_value = "4000")]
+    port: u16,
+
+    /// the address to listen on, `0.0.0.0` opens it to the other machines of the network.
+    #[clap(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// per-route response bodies, eg: `./mocks/UserController/get_one.json`
+    #[clap(short, long)]
+    fixtures: Option<String>,
+}
+
+struct MockResponse {
+    status: u16,
+    content_type: String,
+    body: Option<Value>,
+}
+
+impl Mock {
+    pub fn run(&self) {
+        let builder = match OpenapiBuilder::load(&self.spec) {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Mock] {}", e);
+                std::process::exit(1);
+            }
+        };
+        let mut operations = builder.operations();
+        // literal segments win over templated ones, eg: `/user/me` before `/user/{id}`
+        operations.sort_by_key(|opr| opr.path.matches('{').count());
+
+        let server = match tiny_http::Server::http((self.host.as_str(), self.port)) {
+            Ok(server) => server,
+            Err(e) => {
+                eprintln!("[Mock] listen on {}:{}: {}", self.host, self.port, e);
+                std::process::exit(1);
+            }
+        };
+        println!(
+            "[Mock] serve {} operations on http://{}:{}",
+            operations.len(),
+            self.host,
+            self.port
+        );
+
+        for mut request in server.incoming_requests() {
+            let res = self.handle(&builder, &operations, &mut request);
+            println!(
+                "[Mock] {} {} -> {}",
+                request.method(),
+                request.url(),
+                res.status
+            );
+
+            let body = match &res.body {
+                Some(Value::String(text)) if !res.content_type.contains("json") => text.clone(),
+                Some(body) => body.to_string(),
+                None => String::new(),
+            };
+            let mut response = tiny_http::Response::from_string(body).with_status_code(res.status);
+            for (key, value) in [
+                ("Content-Type", res.content_type.as_str()),
+                ("Access-Control-Allow-Origin", "*"),
+                ("Access-Control-Allow-Methods", "*"),
+                ("Access-Control-Allow-Headers", "*"),
+            ] {
+                response.add_header(
+                    tiny_http::Header::from_bytes(key.as_bytes(), value.as_bytes()).unwrap(),
+                );
+            }
+            if let Err(e) = request.respond(response) {
+                eprintln!("[Mock] respond failed: {}", e);
+            }
+        }
+    }
+
+    fn handle(
+        &self,
+        builder: &OpenapiBuilder,
+        operations: &[Operation],
+        request: &mut tiny_http::Request,
+    ) -> MockResponse {
+        let method = request.method().as_str().to_lowercase();
+        if method == "options" {
+            return MockResponse::json(204, None);
+        }
+
+        let Ok(url) = reqwest::Url::parse(&format!("http://localhost{}", request.url())) else {
+            return MockResponse::json(
+                400,
+                Some(json!({ "message": format!("invalid url {}", request.url()) })),
+            );
+        };
+        let Some((opr, captures)) = find_operation(operations, &method, url.path()) else {
+            return MockResponse::json(
+                404,
+                Some(json!({
+                    "message": format!("no operation matches {} {}", method.to_uppercase(), url.path())
+                })),
+            );
+        };
+
+        let mut query = HashMap::<String, Vec<String>>::new();
+        for (key, value) in url.query_pairs() {
+            query
+                .entry(key.into_owned())
+                .or_default()
+                .push(value.into_owned());
+        }
+
+        let mut errors = param_errors(builder, opr, |location, name| match location {
+            "path" => captures.get(name).cloned(),
+            "query" => query.get(name).map(|values| values.join(",")),
+            "header" => request
+                .headers()
+                .iter()
+                .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+                .map(|h| h.value.to_string()),
+            _ => None,
+        });
+
+        let request_body = builder.resolve(&opr.item["requestBody"]);
+        if request_body.is_object() {
+            let mut body = String::new();
+            let _ = request.as_reader().read_to_string(&mut body);
+            let schema = &request_body["content"]["application/json"]["schema"];
+            if body.trim().is_empty() {
+                if request_body["required"].as_bool().unwrap_or(false) {
+                    errors.push("body: is required".to_string());
+                }
+            } else if !schema.is_null() {
+                match serde_json::from_str::<Value>(&body) {
+                    Ok(value) => {
+                        for e in builder.validate(schema, &value) {
+                            errors.push(e.replacen('$', "body", 1));
+                        }
+                    }
+                    Err(e) => errors.push(format!("body: invalid json, {}", e)),
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return MockResponse::json(
+                400,
+                Some(json!({
+                    "message": "request does not match the openapi spec",
+                    "errors": errors,
+                })),
+            );
+        }
+
+        let (status, response) = builder.success_response(opr.item);
+
+        if let Some(fixtures) = &self.fixtures {
+            let fixture = PathBuf::from(fixtures)
+                .join(opr.controller)
+                .join(format!("{}.json", opr.router));
+            if fixture.exists() {
+                let content = match std::fs::read_to_string(&fixture) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        return MockResponse::json(
+                            500,
+                            Some(
+                                json!({ "message": format!("unreadable fixture {:?}: {}", fixture, e) }),
+                            ),
+                        )
+                    }
+                };
+                return match serde_json::from_str(&content) {
+                    Ok(body) => MockResponse::json(status, Some(body)),
+                    Err(e) => MockResponse::json(
+                        500,
+                        Some(json!({ "message": format!("invalid fixture {:?}: {}", fixture, e) })),
+                    ),
+                };
+            }
+        }
+
+        let Some((content_type, media)) = builder.response_media(response) else {
+            return MockResponse::json(status, None);
+        };
+        let body = builder.media_example(media);
+
+        MockResponse {
+            status,
+            content_type: content_type.to_string(),
+            body: Some(body),
+        }
+    }
+}
+
+impl MockResponse {
+    fn json(status: u16, body: Option<Value>) -> Self {
+        Self {
+            status,
+            content_type: "application/json".to_string(),
+            body,
+        }
+    }
+}
+
+/// The operation of a request and its path params, `operations` are tried in order.
+fn find_operation<'a, 'b>(
+    operations: &'a [Operation<'b>],
+    method: &str,
+    path: &str,
+) -> Option<(&'a Operation<'b>, HashMap<String, String>)> {
+    operations.iter().find_map(|opr| {
+        if opr.method != method {
+            return None;
+        }
+        match_path(opr.path, path).map(|captures| (opr, captures))
+    })
+}
+
+/// Coerce and validate the params of a request, `raw` gives the value of a param by location and name.
+fn param_errors(
+    builder: &OpenapiBuilder,
+    opr: &Operation,
+    raw: impl Fn(&str, &str) -> Option<String>,
+) -> Vec<String> {
+    let mut errors = vec![];
+    for param in builder.parameters(opr) {
+        let name = param["name"].as_str().unwrap_or_default();
+        let location = param["in"].as_str().unwrap_or_default();
+        let Some(raw) = raw(location, name) else {
+            if param["required"].as_bool().unwrap_or(false) {
+                errors.push(format!("{}.{}: is required", location, name));
+            }
+            continue;
+        };
+        let schema = builder.resolve(&param["schema"]);
+        match builder.coerce(schema, &raw) {
+            Ok(value) => {
+                for e in builder.validate(schema, &value) {
+                    errors.push(e.replacen('$', &format!("{}.{}", location, name), 1));
+                }
+            }
+            Err(e) => errors.push(format!("{}.{}: {}", location, name, e)),
+        }
+    }
+    errors
+}
+
+/// Match a request path against a path template, eg: `/user/{id}` and `/user/12`.
+fn match_path(template: &str, path: &str) -> Option<HashMap<String, String>> {
+    let template = template.trim_matches('/').split('/');
+    let path = path.trim_matches('/').split('/');
+    if template.clone().count() != path.clone().count() {
+        return None;
+    }
+
+    let mut captures = HashMap::new();
+    for (t, p) in template.zip(path) {
+        if let Some(name) = t.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+            captures.insert(name.to_string(), percent_decode(p));
+        } else if t != p {
+            return None;
+        }
+    }
+    Some(captures)
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(Ok(b)) = s.get(i + 1..i + 3).map(|hex| u8::from_str_radix(hex, 16)) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_routing() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/user/{id}": {
+                        "get": {
+                            "x-controller": "UserController",
+                            "x-router": "get_one",
+                            "parameters": [
+                                { "name": "id", "in": "path", "required": true, "schema": { "type": "integer" } },
+                                { "name": "tags", "in": "query", "schema": { "type": "array", "items": { "type": "string", "enum": ["a", "b"] } } },
+                                { "name": "x-trace", "in": "header", "required": true, "schema": { "type": "string" } },
+                                { "name": "page", "in": "query", "schema": { "$ref": "#/components/schemas/PageNo" } }
+                            ]
+                        }
+                    },
+                    "/user/me": { "get": { "x-controller": "UserController", "x-router": "me" } }
+                },
+                "components": { "schemas": { "PageNo": { "type": "integer", "minimum": 1 } } }
+            }),
+        };
+        let mut operations = builder.operations();
+        operations.sort_by_key(|opr| opr.path.matches('{').count());
+
+        let (opr, _) = find_operation(&operations, "get", "/user/me/").unwrap();
+        assert_eq!(opr.router, "me");
+        let (opr, captures) = find_operation(&operations, "get", "/user/a%20b").unwrap();
+        assert_eq!(opr.router, "get_one");
+        assert_eq!(captures["id"], "a b");
+        assert!(find_operation(&operations, "post", "/user/1").is_none());
+        assert!(find_operation(&operations, "get", "/user/1/posts").is_none());
+
+        let errors = param_errors(&builder, opr, |location, name| match (location, name) {
+            ("path", _) => Some("12".to_string()),
+            ("query", "page") => Some("2".to_string()),
+            ("query", _) => Some("a,b".to_string()),
+            _ => None,
+        });
+        assert_eq!(errors, vec!["header.x-trace: is required"]);
+        let errors = param_errors(&builder, opr, |location, _| match location {
+            "path" => Some("abc".to_string()),
+            "query" => Some("a,c".to_string()),
+            _ => Some("t".to_string()),
+        });
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].starts_with("path.id: "));
+        assert!(errors[1].starts_with("query.tags[1]: "));
+        assert!(errors[2].starts_with("query.page: "));
+    }
+}
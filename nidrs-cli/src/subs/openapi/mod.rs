@@ -0,0 +1,1355 @@
+mod bundle;
+mod call;
+mod contract;
+mod coverage;
+mod docs;
+mod export;
+mod extract;
+mod hooks;
+mod manifest;
+mod merge;
+mod mock;
+mod msw;
+mod naming;
+mod package;
+mod params;
+mod scaffold;
+mod schema;
+mod schemas;
+mod security;
+mod serve;
+mod servers;
+mod versions;
+
+use std::{collections::BTreeMap, io::Write, path::PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use crate::shared::exec_cmd;
+
+use manifest::{Manifest, MANIFEST_FILE};
+use naming::{ts_ident, ts_member, ts_property_key, ts_string};
+use params::{path_params, ParamStyle};
+
+pub use call::Call;
+
+/// eg: nid openapi "http://localhost:3000" --yes
+#[derive(clap::Parser, Debug)]
+#[clap(args_conflicts_with_subcommands = true)]
+pub struct Openapi {
+    #[clap(subcommand)]
+    command: Option<OpenapiCommands>,
+
+    /// nidrs server address, openapi spec file or nidrs project dir.
+    #[clap(default_value = "http://localhost:3000")]
+    serve: String,
+
+    #[clap(short, long, default_value = "./node_modules/@nidist/api-client")]
+    out_dir: Option<String>,
+
+    /// default: ts
+    #[clap(short, long)]
+    lang: Option<String>,
+
+    #[clap(short, long)]
+    yes: bool,
+
+    /// overwrite files edited by hand, or an `out_dir` not generated by nid.
+    #[clap(long)]
+    force: bool,
+
+    /// compare the generated client with `out_dir`, print a diff and exit 1 on drift, without writing.
+    #[clap(long)]
+    check: bool,
+
+    /// also generate msw handlers and fixture factories for frontend tests.
+    #[clap(long)]
+    msw: bool,
+
+    /// also generate typed data fetching hooks per operation.
+    #[clap(long, value_enum)]
+    hooks: Option<hooks::Hooks>,
+
+    /// how the generated methods take their parameters.
+    #[clap(long, value_enum, default_value = "flat")]
+    params: ParamStyle,
+
+    /// merge several services into one client instead of `serve`, eg: --service billing=http://localhost:3001
+    #[clap(long = "service", value_parser = merge::parse_key_value)]
+    services: Vec<(String, String)>,
+
+    /// the base url or gateway prefix of a service, eg: --base-url billing=/billing
+    #[clap(long = "base-url", value_parser = merge::parse_key_value)]
+    base_urls: Vec<(String, String)>,
+
+    /// which versions the client gets when the paths or `x-version` give several, eg: `api.v1.user` and `api.v2.user`.
+    #[clap(long, value_enum, default_value = "all")]
+    versions: versions::Versions,
+
+    /// save the loaded spec after bundling and normalization, as json or yaml by its extension,
+    /// eg: --emit-spec openapi.yaml. It can be given back as `serve` for offline runs.
+    #[clap(long)]
+    emit_spec: Option<String>,
+
+    /// the project config, its `[openapi.servers]` override the servers of the spec per env.
+    #[clap(long, default_value = servers::CONFIG_FILE)]
+    config: PathBuf,
+
+    #[clap(flatten)]
+    pkg: package::PackageArgs,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum OpenapiCommands {
+    /// serve a local mock server from the openapi spec.
+    Mock(mock::Mock),
+
+    /// export the spec as a postman/insomnia collection or a `.http` file.
+    Export(export::Export),
+
+    /// render the spec as a markdown or static html api reference.
+    Docs(docs::Docs),
+
+    /// pack a package generated with `--package` into a tarball.
+    Pack(package::Pack),
+
+    /// extract the spec from the sources of a nidrs project, without running it.
+    Extract(extract::Extract),
+
+    /// call a live server and check its responses against the spec.
+    Test(contract::Test),
+
+    /// generate nidrs modules, controllers and dtos from the spec, keeping what is implemented.
+    Scaffold(scaffold::Scaffold),
+
+    /// write a Draft 2020-12 json schema per component schema and per operation body.
+    Schemas(schemas::Schemas),
+
+    /// report how well the operations are documented, per controller and overall.
+    Coverage(coverage::Coverage),
+
+    /// browse the spec in a local explorer, reloaded when the spec changes.
+    Serve(serve::Serve),
+}
+
+impl OpenapiCommands {
+    pub fn run(&self) {
+        match self {
+            OpenapiCommands::Mock(mock) => mock.run(),
+            OpenapiCommands::Export(export) => export.run(),
+            OpenapiCommands::Docs(docs) => docs.run(),
+            OpenapiCommands::Pack(pack) => pack.run(),
+            OpenapiCommands::Extract(extract) => extract.run(),
+            OpenapiCommands::Test(test) => test.run(),
+            OpenapiCommands::Scaffold(scaffold) => scaffold.run(),
+            OpenapiCommands::Schemas(schemas) => schemas.run(),
+            OpenapiCommands::Coverage(coverage) => coverage.run(),
+            OpenapiCommands::Serve(serve) => serve.run(),
+        }
+    }
+}
+
+impl Openapi {
+    pub fn run(&self) {
+        if let Some(command) = &self.command {
+            return command.run();
+        }
+
+        let openapi_json = match self.load() {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Openapi] {}", e);
+                std::process::exit(1);
+            }
+        };
+        // println!("{:?}", openapi_json.openapi);
+        let files = match self.generate(&openapi_json) {
+            Ok(files) => files,
+            Err(e) => {
+                eprintln!("[Openapi] {}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let out_dir = self.out_dir.as_ref().unwrap();
+        let out_dir_path = PathBuf::from(out_dir);
+        // a package builds at its root, the api-client template builds in `client`
+        let build_dir_path = if self.pkg.package {
+            out_dir_path.clone()
+        } else {
+            out_dir_path.join("client")
+        };
+
+        if self.check {
+            let drift = check(&out_dir_path, &files);
+            if drift {
+                eprintln!(
+                    "[Openapi] {:?} is out of date, run `nid openapi` to regenerate it.",
+                    out_dir_path
+                );
+                std::process::exit(1);
+            }
+            println!("[Openapi] {:?} is up to date.", out_dir_path);
+            return;
+        }
+
+        let manifest = match Manifest::read(&out_dir_path) {
+            Some(manifest) => manifest,
+            None if out_dir_path.exists() => {
+                // generated before the manifest existed
+                let pkg_json =
+                    std::fs::read_to_string(out_dir_path.join("package.json")).unwrap_or_default();
+                if !pkg_json.contains("templates/api-client") && !self.force {
+                    eprintln!(
+                        "[Openapi] {:?} is not generated by nid, pass --force to overwrite it.",
+                        out_dir_path
+                    );
+                    std::process::exit(1);
+                }
+                if !self.yes {
+                    let mut input = String::new();
+                    print!(
+                        "[Openapi] {:?} has no {}, take it over? (y/n): ",
+                        out_dir_path.display(),
+                        MANIFEST_FILE
+                    );
+                    std::io::stdout().flush().unwrap();
+                    std::io::stdin().read_line(&mut input).unwrap();
+                    if !input.contains("y") {
+                        return;
+                    }
+                }
+                Manifest::adopt(&out_dir_path, &files)
+            }
+            None if self.pkg.package => {
+                std::fs::create_dir_all(&out_dir_path).unwrap();
+                Manifest::new(&[])
+            }
+            None => {
+                let template_url = "https://github.com/nidrs/tempalte-client-js";
+                // git clone
+                exec_cmd(
+                    "Git",
+                    std::process::Command::new("git")
+                        .arg("clone")
+                        .arg("--progress")
+                        .arg(template_url)
+                        .arg(&out_dir_path),
+                )
+                .unwrap();
+
+                // remove .git
+                let git_path = out_dir_path.join(".git");
+
+                let _ = std::fs::remove_dir_all(git_path);
+
+                // the template files the generation replaces are ours too
+                Manifest::adopt(&out_dir_path, &files)
+            }
+        };
+
+        let sync = manifest.sync(&out_dir_path, &files, self.force);
+        if !sync.edited.is_empty() {
+            for file in &sync.edited {
+                println!("[Openapi] edited by hand: {}", file.display());
+            }
+            if !self.force {
+                eprintln!("[Openapi] pass --force to overwrite the edited files.");
+                std::process::exit(1);
+            }
+        }
+
+        for (file, content) in files.iter().filter(|(file, _)| sync.write.contains(file)) {
+            println!("[Openapi] write {}", file.display());
+            let path = out_dir_path.join(file);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            let mut file = std::fs::File::create(path).unwrap();
+            file.write_all(content.as_bytes()).unwrap();
+        }
+        for file in &sync.remove {
+            println!("[Openapi] remove {}", file.display());
+            let _ = std::fs::remove_file(out_dir_path.join(file));
+        }
+        Manifest::new(&files).write(&out_dir_path).unwrap();
+
+        if sync.write.is_empty() && sync.remove.is_empty() {
+            println!("[Openapi] {:?} is up to date.", out_dir_path);
+            return;
+        }
+
+        // a regenerated package.json may ask for another runtime or peers than the ones installed
+        let pkg_json = build_dir_path.join("package.json");
+        let pkg_json_changed = sync
+            .write
+            .iter()
+            .any(|file| out_dir_path.join(file) == pkg_json);
+        if pkg_json_changed || !build_dir_path.join("node_modules").exists() {
+            exec_cmd(
+                "Build(0)",
+                std::process::Command::new("npm")
+                    .arg("install")
+                    .current_dir(&build_dir_path),
+            )
+            .unwrap();
+        }
+
+        exec_cmd(
+            "Build(1)",
+            std::process::Command::new("npm")
+                .arg("run")
+                .arg("build")
+                .current_dir(&build_dir_path),
+        )
+        .unwrap();
+
+        // exec_cmd(
+        //     "Link",
+        //     std::process::Command::new("npm")
+        //         .arg("link")
+        //         .current_dir(&client_dir_path),
+        // )
+        // .unwrap();
+
+        println!("[Openapi] build api client sdk: {:?}", out_dir_path);
+    }
+
+    /// Load `serve`, or merge the `--service`s into one spec, then apply the server overrides of `config`,
+    /// emit the spec and apply the version namespaces.
+    fn load(&self) -> Result<OpenapiBuilder, anyhow::Error> {
+        let mut builder = if self.services.is_empty() {
+            OpenapiBuilder::load(&self.serve)?
+        } else {
+            self.merge()?
+        };
+        builder.override_servers(&servers::read_overrides(&self.config)?)?;
+        if let Some(file) = &self.emit_spec {
+            std::fs::write(file, bundle::to_spec_text(&builder.openapi, file)?)
+                .map_err(|e| anyhow::anyhow!("write {}: {}", file, e))?;
+            println!("[Openapi] write spec {}", file);
+        }
+        // the emitted spec is left unversioned, versions are a choice of the client
+        builder.version_namespaces(self.versions);
+        Ok(builder)
+    }
+
+    fn merge(&self) -> Result<OpenapiBuilder, anyhow::Error> {
+        let mut services = vec![];
+        for (namespace, source) in &self.services {
+            println!("[Openapi] load {} <- {}", namespace, source);
+            services.push(merge::Service {
+                namespace: namespace.clone(),
+                builder: OpenapiBuilder::load(source)?,
+                base_url: self
+                    .base_urls
+                    .iter()
+                    .find(|(name, _)| name == namespace)
+                    .map(|(_, base_url)| base_url.clone()),
+            });
+        }
+        if let Some((name, _)) = self
+            .base_urls
+            .iter()
+            .find(|(name, _)| !self.services.iter().any(|(namespace, _)| namespace == name))
+        {
+            return Err(anyhow::anyhow!(
+                "--base-url of an unknown service: {}",
+                name
+            ));
+        }
+        OpenapiBuilder::merge(&services)
+    }
+
+    /// The generated files, relative to `out_dir`.
+    fn generate(&self, builder: &OpenapiBuilder) -> Result<Vec<(PathBuf, String)>, anyhow::Error> {
+        builder.check_names()?;
+        if self.hooks.is_some() && self.params == ParamStyle::Positional {
+            return Err(anyhow::anyhow!(
+                "--hooks takes the dto of an operation, use it with --params flat or structured"
+            ));
+        }
+
+        let mut files = vec![(PathBuf::from("client/index.ts"), builder.to_ts(self.params))];
+        if self.msw {
+            files.push((PathBuf::from("client/msw.ts"), builder.to_msw()));
+            files.push((PathBuf::from("client/fixtures.ts"), builder.to_fixtures()));
+        }
+        if let Some(hooks) = self.hooks {
+            files.push((PathBuf::from("client/hooks.ts"), builder.to_hooks(hooks)));
+        }
+        if self.pkg.package {
+            let out_dir = PathBuf::from(self.out_dir.as_ref().unwrap());
+            files.extend(self.pkg.files(builder, &files, &out_dir));
+        }
+        Ok(files)
+    }
+}
+
+pub struct OpenapiBuilder {
+    pub openapi: serde_json::Value,
+}
+
+/// An operation of the spec, grouped by its `x-controller` and `x-router`.
+#[derive(Debug, Clone, Copy)]
+pub struct Operation<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub controller: &'a str,
+    pub router: &'a str,
+    pub item: &'a Value,
+}
+
+/// The version of the route table contract with `reqHandler` of `@nidrs/openapi-client-js`.
+pub const ROUTES_VERSION: u64 = 2;
+
+const HTTP_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+impl OpenapiBuilder {
+    /// Load the spec from a nidrs server address, a spec url or a local file.
+    ///
+    /// eg: `http://localhost:3000`, `http://localhost:3000/api-docs/openapi.json`, `./openapi.json`, `./openapi.yaml`
+    ///
+    /// The external `$ref`s of a file are bundled into its `components`.
+    pub fn load(source: &str) -> Result<Self, anyhow::Error> {
+        if std::path::Path::new(source).is_dir() {
+            return Self::extract(std::path::Path::new(source));
+        }
+        let mut builder = if source.starts_with("http://") || source.starts_with("https://") {
+            let url = if source.ends_with(".json") || bundle::is_yaml(source) {
+                source.to_string()
+            } else {
+                format!("{}/api-docs/openapi.json", source.trim_end_matches('/'))
+            };
+            let text = reqwest::blocking::get(&url)?.error_for_status()?.text()?;
+            Self {
+                openapi: bundle::parse_spec(&text, &url)?,
+            }
+        } else {
+            let text = std::fs::read_to_string(source)
+                .map_err(|e| anyhow::anyhow!("read {}: {}", source, e))?;
+            let mut builder = Self {
+                openapi: bundle::parse_spec(&text, source)?,
+            };
+            let base = std::path::Path::new(source)
+                .parent()
+                .unwrap_or(std::path::Path::new(""));
+            builder.bundle(base)?;
+            builder
+        };
+        builder.normalize();
+        Ok(builder)
+    }
+
+    /// All operations of the spec which carry `x-controller` and `x-router`.
+    pub fn operations(&self) -> Vec<Operation<'_>> {
+        let mut operations = vec![];
+        if let Some(paths) = self.openapi["paths"].as_object() {
+            for (path, item_path) in paths {
+                let Some(item) = item_path.as_object() else {
+                    continue;
+                };
+                for (method, opr) in item {
+                    if !HTTP_METHODS.contains(&method.as_str()) {
+                        continue;
+                    }
+                    let (Some(controller), Some(router)) =
+                        (opr["x-controller"].as_str(), opr["x-router"].as_str())
+                    else {
+                        continue;
+                    };
+                    operations.push(Operation {
+                        method,
+                        path,
+                        controller,
+                        router,
+                        item: opr,
+                    });
+                }
+            }
+        }
+        operations
+    }
+
+    /// Operations grouped by their `x-controller`, in a stable order.
+    pub fn controllers(&self) -> BTreeMap<&str, Vec<Operation<'_>>> {
+        let mut controllers = BTreeMap::<&str, Vec<Operation>>::new();
+        for opr in self.operations() {
+            controllers.entry(opr.controller).or_default().push(opr);
+        }
+        for operations in controllers.values_mut() {
+            operations.sort_by_key(|opr| opr.router);
+        }
+        controllers
+    }
+
+    /// The resolved parameters of an operation, including the ones declared on its path.
+    pub fn parameters<'a>(&'a self, opr: &Operation<'a>) -> Vec<&'a Value> {
+        let parameters = opr.item["parameters"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|param| self.resolve(param))
+            .collect::<Vec<_>>();
+        // an operation parameter overrides the path parameter with the same name and location
+        let path_parameters = self.openapi["paths"][opr.path]["parameters"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|param| self.resolve(param))
+            .filter(|param| {
+                !parameters
+                    .iter()
+                    .any(|p| p["name"] == param["name"] && p["in"] == param["in"])
+            });
+        path_parameters.chain(parameters.iter().copied()).collect()
+    }
+
+    /// The compact route table `reqHandler` needs at runtime, instead of the whole spec.
+    ///
+    /// eg: `{"version":2,"paths":{"/user/{id}":{"get":{"params":{"path":["id"]},"accept":"application/json"}}}}`
+    pub fn to_routes(&self) -> Value {
+        let mut paths = Map::new();
+        for opr in self.operations() {
+            let mut route = Map::new();
+
+            let mut params = Map::new();
+            for param in self.parameters(&opr) {
+                let (Some(name), Some(location)) = (param["name"].as_str(), param["in"].as_str())
+                else {
+                    continue;
+                };
+                if let Value::Array(names) = params.entry(location).or_insert(json!([])) {
+                    names.push(json!(name));
+                }
+            }
+            // the path params the spec does not declare are still filled in
+            for param in self.path_parameters(&opr) {
+                if let Value::Array(names) = params.entry("path").or_insert(json!([])) {
+                    if !names.contains(&param["name"]) {
+                        names.push(param["name"].clone());
+                    }
+                }
+            }
+            if !params.is_empty() {
+                route.insert("params".to_string(), Value::Object(params));
+            }
+
+            let request_body = self.resolve(&opr.item["requestBody"]);
+            if let Some((content_type, media)) = self.response_media(request_body) {
+                let schema = self.resolve(&media["schema"]);
+                let mut body = vec![];
+                let all_of = schema["allOf"].as_array().into_iter().flatten();
+                for schema in std::iter::once(schema).chain(all_of.map(|s| self.resolve(s))) {
+                    if let Some(properties) = schema["properties"].as_object() {
+                        body.extend(properties.keys().map(|name| json!(name)));
+                    }
+                }
+                route.insert("contentType".to_string(), json!(content_type));
+                route.insert("body".to_string(), Value::Array(body));
+            }
+
+            let accept = match self.stream_media(opr.item) {
+                Some((_, content_type, _)) => Some(content_type),
+                None => opr.item["responses"].as_object().and_then(|responses| {
+                    responses
+                        .values()
+                        .find_map(|response| self.response_media(response))
+                        .map(|(content_type, _)| content_type)
+                }),
+            };
+            if let Some(accept) = accept {
+                route.insert("accept".to_string(), json!(accept));
+            }
+
+            let security = self.security(opr.item);
+            if !security.is_empty() {
+                route.insert("security".to_string(), json!(security));
+            }
+
+            if let Value::Object(methods) = paths.entry(opr.path).or_insert(json!({})) {
+                methods.insert(opr.method.to_string(), Value::Object(route));
+            }
+        }
+        let mut routes = json!({ "version": ROUTES_VERSION, "paths": paths });
+        if let Some(schemes) = self.to_route_schemes() {
+            routes["schemes"] = schemes;
+        }
+        routes
+    }
+
+    pub fn to_ts(&self, style: ParamStyle) -> String {
+        let mut controllers = BTreeMap::new();
+
+        for opr in self.operations() {
+            let controller: &mut BTreeMap<_, _> = controllers.entry(opr.controller).or_default();
+
+            controller.insert(opr.router, opr);
+        }
+
+        // println!("{:#?}", controllers);
+
+        // streamHandler, authHandler, serverAdapt, structured dtos and cookie params need a newer runtime,
+        // only the clients using them ask for it
+        let streaming = self
+            .operations()
+            .iter()
+            .any(|opr| self.stream_media(opr.item).is_some());
+        let auth = !self.security_schemes().is_empty();
+        let servers = !self.environments().is_empty();
+        let cookies = self.operations().iter().any(|opr| {
+            self.parameters(opr)
+                .iter()
+                .any(|param| param["in"] == "cookie")
+        });
+        let runtime = if style != ParamStyle::Flat || cookies {
+            "0.7.0"
+        } else if servers {
+            "0.6.0"
+        } else if auth {
+            "0.5.0"
+        } else if streaming {
+            "0.4.0"
+        } else {
+            "0.3.0"
+        };
+        let mut ts = format!(
+            "// @ts-nocheck eslint-disable prettier-ignore\nimport {{ reqHandler, resHandler{}{}{} }} from \"@nidrs/openapi-client-js\";\n",
+            if streaming { ", streamHandler" } else { "" },
+            if auth { ", authHandler, type Credential" } else { "" },
+            if servers { ", serverAdapt" } else { "" }
+        );
+        ts.push_str(&format!(
+            "// routes v{}, requires @nidrs/openapi-client-js >= {}\n\n",
+            ROUTES_VERSION, runtime
+        ));
+        // stream items and structured bodies reference the component schemas by name
+        if runtime != "0.3.0" {
+            if let Some(schemas) = self.openapi["components"]["schemas"].as_object() {
+                for (name, schema) in schemas {
+                    ts.push_str(&format!(
+                        "export type {} = {};\n",
+                        ts_ident(name),
+                        self.ts_type(schema)
+                    ));
+                }
+                ts.push('\n');
+            }
+        }
+
+        if auth || servers {
+            let mut options = String::new();
+            if servers {
+                let (env, members) = self.ts_servers();
+                ts.push_str(&env);
+                options.push_str(&members);
+            }
+            if auth {
+                ts.push_str(&self.ts_auth());
+                options.push_str("  auth?: ApiAuth;\n");
+            }
+            ts.push_str(&format!(
+                "/* prettier-ignore */\nexport interface ApiOptions {{\n{}}}\n",
+                options
+            ));
+        }
+
+        for (controller, router) in &controllers {
+            ts.push_str(&format!(
+                "/* prettier-ignore */\nexport class {} {{\n",
+                class_name(controller)
+            ));
+            ts.push_str("  constructor(private api: Api) {}\n");
+
+            for (router, opr) in router {
+                let method = opr.method;
+                let path = opr.path;
+                let mut dto_keys = BTreeMap::<&str, Vec<serde_json::Value>>::new();
+                for param in self.parameters(opr) {
+                    let Some(name) = param["name"].as_str() else {
+                        continue;
+                    };
+                    dto_keys.entry(name).or_default().push(param.to_owned());
+                }
+                for (name, param) in path_params(opr.path)
+                    .into_iter()
+                    .zip(self.path_parameters(opr))
+                {
+                    let keys = dto_keys.entry(name).or_default();
+                    if !keys.iter().any(|key| key["in"] == "path") {
+                        keys.push(param);
+                    }
+                }
+
+                let request_body = self.resolve(&opr.item["requestBody"]);
+                let content = self.resolve(&request_body["content"]["application/json"]["schema"]);
+                if let Some(properties) = content["properties"].as_object() {
+                    for (name, schema) in properties {
+                        let keys = dto_keys.entry(name).or_default();
+                        keys.push(self.body_key(content, name, schema));
+                    }
+                }
+
+                let dto_types = if dto_keys.is_empty() {
+                    "any".to_string()
+                } else {
+                    self.ts_members(&dto_keys)
+                };
+
+                let mut resp_body = BTreeMap::<&str, Vec<serde_json::Value>>::new();
+
+                let responses = ["200", "201"]
+                    .iter()
+                    .map(|code| {
+                        &opr.item["responses"][code]["content"]["application/json"]["schema"]
+                    })
+                    .find(|schema| schema.is_object())
+                    .map(|schema| self.resolve(schema));
+
+                if let Some(properties) = responses.and_then(|r| r["properties"].as_object()) {
+                    for (name, schema) in properties {
+                        let keys = resp_body.entry(name).or_default();
+                        keys.push(self.body_key(responses.unwrap(), name, schema));
+                    }
+                }
+
+                let resp_body = if resp_body.is_empty() {
+                    "any".to_string()
+                } else {
+                    self.ts_members(&resp_body)
+                };
+
+                let (args, dto) = self.ts_args(style, opr, &dto_types);
+                let handler_arg = style.handler_arg();
+                let mut req = format!(
+                    "reqHandler({dto}, '{method}', '{path}', this.api.routes{handler_arg})"
+                );
+                // credentials only go to the operations which require them
+                if !self.security(opr.item).is_empty() {
+                    req = format!(
+                        "await authHandler({req}, '{method}', '{path}', this.api.routes, this.api.options.auth)"
+                    );
+                }
+                ts.push_str(&self.ts_doc(controller, router, opr.item, path, &dto_keys, style));
+                if let Some((stream, _, item)) = self.stream_media(opr.item) {
+                    let item = self.ts_type(item);
+                    ts.push_str(&format!(
+                        "  async *{}({args}): AsyncGenerator<{item}> {{\n",
+                        ts_member(router)
+                    ));
+                    ts.push_str(&format!(
+                        "    yield* streamHandler<{item}>(await this.api.request({req}), '{}')\n",
+                        stream.as_str()
+                    ));
+                } else {
+                    ts.push_str(&format!("  async {}({args}) {{\n", ts_member(router)));
+                    ts.push_str(&format!(
+                        "    return resHandler<{resp_body}>(await this.api.request({req}))\n"
+                    ));
+                }
+                ts.push_str("  }\n");
+            }
+            ts.push_str("}\n");
+        }
+
+        ts.push_str("/* prettier-ignore */\nexport class Api {\n");
+        let members = self
+            .controllers()
+            .into_keys()
+            .map(|controller| (api_path(controller), class_name(controller)))
+            .collect::<Vec<_>>();
+        ts.push_str(&ts_api_members(&members, 1));
+        ts.push_str(&format!("  routes = {};\n", self.to_routes()));
+        if servers {
+            ts.push_str(&format!("  servers = {};\n", self.to_servers()));
+            ts.push_str("  request: any;\n");
+            ts.push_str("  constructor(request: any, public options: ApiOptions = {}) {\n");
+            ts.push_str("    this.request = serverAdapt(request, this.servers, options);\n");
+            ts.push_str("  }\n");
+        } else if auth {
+            ts.push_str("  constructor(public request: any, public options: ApiOptions = {}) {}\n");
+        } else {
+            ts.push_str("  constructor(public request: any) {}\n");
+        }
+        ts.push_str("}\n");
+
+        ts
+    }
+
+    /// A body property as a dto key, carrying what the member and its docs need.
+    fn body_key(&self, object: &Value, name: &str, schema: &Value) -> Value {
+        let required = object["required"]
+            .as_array()
+            .map(|r| r.iter().any(|r| r == name))
+            .unwrap_or(false);
+        let resolved = self.resolve(schema);
+        json!({
+            "in": "body",
+            "schema": resolved,
+            "required": required,
+            "description": schema.get("description").or(resolved.get("description")),
+            "deprecated": schema.get("deprecated").or(resolved.get("deprecated")),
+        })
+    }
+
+    /// Render dto keys as the members of a ts object type, documented from their schemas.
+    fn ts_members(&self, dto_keys: &BTreeMap<&str, Vec<Value>>) -> String {
+        let mut types = "{\n".to_string();
+
+        for (name, keys) in dto_keys {
+            for key in keys {
+                let t_type = trans_to_ts_type(
+                    self.resolve(&key["schema"])["type"]
+                        .as_str()
+                        .unwrap_or("any"),
+                );
+                let t_required = key["required"].as_bool().unwrap_or(false);
+                let t_required = if t_required { "" } else { "?" };
+                let t_key = if keys.len() > 1 {
+                    format!("{}({})", key["in"].as_str().unwrap(), name)
+                } else {
+                    name.to_string()
+                };
+                types.push_str(&jsdoc(&self.key_doc(key), "    "));
+                types.push_str(&format!(
+                    "    [{}]{}: {},\n",
+                    ts_string(&t_key),
+                    t_required,
+                    t_type
+                ));
+            }
+            if keys.len() > 1 {
+                types.push_str(&format!("    [{}]?: any,\n", ts_string(name)));
+            }
+        }
+
+        types.push_str("  }");
+
+        types
+    }
+
+    /// Doc lines of a dto key: description, deprecation, default and example.
+    fn key_doc(&self, key: &Value) -> Vec<String> {
+        let schema = self.resolve(&key["schema"]);
+        let mut lines = vec![];
+        if let Some(description) = key["description"]
+            .as_str()
+            .or(schema["description"].as_str())
+        {
+            lines.extend(description.lines().map(|l| l.to_string()));
+        }
+        if key["deprecated"].as_bool().unwrap_or(false)
+            || schema["deprecated"].as_bool().unwrap_or(false)
+        {
+            lines.push("@deprecated".to_string());
+        }
+        if let Some(default) = schema.get("default") {
+            lines.push(format!("@default {}", default));
+        }
+        if let Some(example) = key.get("example").or(schema.get("example")) {
+            lines.push(format!("@example {}", example));
+        }
+        lines
+    }
+
+    /// The doc comment of an operation: summary, description, `@param`s, `@deprecated` and `@example`.
+    fn ts_doc(
+        &self,
+        controller: &str,
+        router: &str,
+        opr: &Value,
+        path: &str,
+        dto_keys: &BTreeMap<&str, Vec<Value>>,
+        style: ParamStyle,
+    ) -> String {
+        let mut lines = vec![];
+        for text in [&opr["summary"], &opr["description"]] {
+            if let Some(text) = text.as_str().filter(|t| !t.trim().is_empty()) {
+                if !lines.is_empty() {
+                    lines.push(String::new());
+                }
+                lines.extend(text.lines().map(|l| l.to_string()));
+            }
+        }
+
+        let mut params = vec![];
+        // the flat dto, or the dto of each location in the structured styles
+        let mut example = Map::new();
+        let mut locations = BTreeMap::<String, Map<String, Value>>::new();
+        let mut positional = BTreeMap::new();
+        for (name, keys) in dto_keys {
+            for key in keys {
+                let location = key["in"].as_str().unwrap_or_default();
+                let e_key = if keys.len() > 1 && style == ParamStyle::Flat {
+                    format!("{}({})", location, name)
+                } else {
+                    name.to_string()
+                };
+                let t_key = match style {
+                    ParamStyle::Positional if location == "path" => ts_ident(name),
+                    ParamStyle::Flat if keys.len() > 1 || ts_member(name) != *name => {
+                        format!("dto[\"{}\"]", e_key)
+                    }
+                    ParamStyle::Flat => format!("dto.{}", e_key),
+                    _ if ts_member(name) == *name => format!("dto.{}.{}", location, name),
+                    _ => format!("dto.{}[\"{}\"]", location, name),
+                };
+                let schema = self.resolve(&key["schema"]);
+                let mut text = key["description"]
+                    .as_str()
+                    .or(schema["description"].as_str())
+                    .unwrap_or_default()
+                    .replace('\n', " ");
+                if let Some(default) = schema.get("default") {
+                    text.push_str(&format!(" (default: {})", default));
+                }
+                if !text.trim().is_empty() {
+                    params.push(format!("@param {} {}", t_key, text.trim()));
+                }
+                if let Some(value) = key.get("example").or(schema.get("example")) {
+                    match style {
+                        ParamStyle::Flat => {
+                            example.insert(e_key, value.clone());
+                        }
+                        ParamStyle::Positional if location == "path" => {
+                            positional.insert(*name, value.clone());
+                        }
+                        _ => {
+                            locations
+                                .entry(location.to_string())
+                                .or_default()
+                                .insert(e_key, value.clone());
+                        }
+                    }
+                }
+            }
+        }
+        if !params.is_empty() {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.extend(params);
+        }
+        let request_body = self.resolve(&opr["requestBody"]);
+        if let Some(body) = self
+            .response_media(request_body)
+            .and_then(|(_, media)| media.get("example"))
+        {
+            match (style, body) {
+                (ParamStyle::Flat, Value::Object(body)) => example.extend(body.clone()),
+                (ParamStyle::Flat, _) => {}
+                _ => {
+                    example.insert("body".to_string(), body.clone());
+                    locations.remove("body");
+                }
+            }
+        }
+        for (location, values) in locations {
+            example.insert(location, Value::Object(values));
+        }
+
+        if !self.security_schemes().is_empty() {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(self.security_doc(opr));
+        }
+        if opr["deprecated"].as_bool().unwrap_or(false) {
+            lines.push("@deprecated".to_string());
+        }
+        let mut args = path_params(path)
+            .into_iter()
+            .filter_map(|name| positional.get(name).map(|v| v.to_string()))
+            .collect::<Vec<_>>();
+        if !example.is_empty() {
+            args.push(Value::Object(example).to_string());
+        }
+        if !args.is_empty() {
+            lines.push("@example".to_string());
+            lines.push(format!(
+                "await api.{}.{}({})",
+                api_path(controller),
+                ts_member(router),
+                args.join(", ")
+            ));
+        }
+
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        jsdoc(&lines, "  ")
+    }
+
+    /// Render the schema as a ts type, component schemas are referenced by their name.
+    pub fn ts_type(&self, schema: &Value) -> String {
+        self.ts_type_depth(schema, 0)
+    }
+
+    fn ts_type_depth(&self, schema: &Value, depth: usize) -> String {
+        if let Some(name) = schema["$ref"]
+            .as_str()
+            .and_then(|r| r.strip_prefix("#/components/schemas/"))
+        {
+            return ts_ident(name);
+        }
+        let schema = self.resolve(schema);
+        if depth > 8 || !schema.is_object() {
+            return "any".to_string();
+        }
+
+        let union = |items: &Vec<Value>, sep: &str| {
+            items
+                .iter()
+                .map(|item| self.ts_type_depth(item, depth + 1))
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+
+        let t = if let Some(values) = schema["enum"].as_array() {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(" | ")
+        } else if let Some(items) = schema["oneOf"]
+            .as_array()
+            .or_else(|| schema["anyOf"].as_array())
+        {
+            union(items, " | ")
+        } else if let Some(items) = schema["allOf"].as_array() {
+            union(items, " & ")
+        } else {
+            match schema::schema_type(schema) {
+                "string" if schema["format"] == "binary" => "Blob".to_string(),
+                "string" => "string".to_string(),
+                "integer" | "number" => "number".to_string(),
+                "boolean" => "boolean".to_string(),
+                "null" => "null".to_string(),
+                "array" => format!("Array<{}>", self.ts_type_depth(&schema["items"], depth + 1)),
+                "object" => {
+                    let required = schema["required"].as_array();
+                    let is_required = |name: &str| {
+                        required
+                            .map(|r| r.iter().any(|r| r == name))
+                            .unwrap_or(false)
+                    };
+                    let mut fields = vec![];
+                    if let Some(properties) = schema["properties"].as_object() {
+                        for (name, property) in properties {
+                            fields.push(format!(
+                                "{}{}: {}",
+                                ts_property_key(name),
+                                if is_required(name) { "" } else { "?" },
+                                self.ts_type_depth(property, depth + 1)
+                            ));
+                        }
+                    }
+                    if schema["additionalProperties"].is_object() {
+                        fields.push(format!(
+                            "[key: string]: {}",
+                            self.ts_type_depth(&schema["additionalProperties"], depth + 1)
+                        ));
+                    }
+                    if fields.is_empty() {
+                        "Record<string, any>".to_string()
+                    } else {
+                        format!("{{ {} }}", fields.join("; "))
+                    }
+                }
+                _ => "any".to_string(),
+            }
+        };
+
+        if schema["nullable"].as_bool().unwrap_or(false) {
+            format!("{} | null", t)
+        } else {
+            t
+        }
+    }
+}
+
+/// Render the lines as a doc comment, `*/` in the text is escaped.
+fn jsdoc(lines: &[String], indent: &str) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut doc = format!("{}/**\n", indent);
+    for line in lines {
+        let line = line.replace("*/", "*\\/");
+        if line.is_empty() {
+            doc.push_str(&format!("{} *\n", indent));
+        } else {
+            doc.push_str(&format!("{} * {}\n", indent, line));
+        }
+    }
+    doc.push_str(&format!("{} */\n", indent));
+    doc
+}
+
+/// The flat name of a controller, eg: `UserController` -> `user`, `billing.InvoiceController` -> `billingInvoice`.
+fn api_key(controller: &str) -> String {
+    let path = api_path(controller);
+    let mut keys = path.split('.');
+    let first = keys.next().unwrap_or_default().to_string();
+    ts_member(&keys.fold(first, |key, next| key + &to_pascal_case(next)))
+}
+
+/// The property path of a controller on the generated `Api` class, eg: `billing.InvoiceController` -> `billing.invoice`.
+///
+/// Namespaces nest, eg: `billing.v2.InvoiceController` -> `billing.v2.invoice`.
+fn api_path(controller: &str) -> String {
+    let (namespaces, controller) = split_namespaces(controller);
+    namespaces
+        .iter()
+        .map(|namespace| ts_member(&to_camel_case(namespace)))
+        .chain(std::iter::once(ts_member(
+            &to_camel_case(controller).replace("Controller", ""),
+        )))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// The generated class of a controller, eg: `billing.InvoiceController` -> `BillingInvoiceController`.
+fn class_name(controller: &str) -> String {
+    let (namespaces, controller) = split_namespaces(controller);
+    let namespaces: String = namespaces.iter().map(|n| to_pascal_case(n)).collect();
+    ts_ident(&format!("{}{}", namespaces, controller))
+}
+
+/// The namespaces and the name of a controller, eg: `billing.v2.InvoiceController` -> `[billing, v2]`, `InvoiceController`.
+fn split_namespaces(controller: &str) -> (Vec<&str>, &str) {
+    match controller.rsplit_once('.') {
+        Some((namespaces, controller)) => (namespaces.split('.').collect(), controller),
+        None => (vec![], controller),
+    }
+}
+
+/// The controller members of `Api`, namespaces as nested objects, eg:
+/// `user = new UserController(this);` and `v2 = {\n    user: new V2UserController(this),\n  };`
+fn ts_api_members(members: &[(String, String)], depth: usize) -> String {
+    let indent = "  ".repeat(depth);
+    let mut namespaces = BTreeMap::<&str, Vec<(String, String)>>::new();
+    let mut ts = String::new();
+    for (path, class) in members {
+        match path.split_once('.') {
+            Some((namespace, rest)) => namespaces
+                .entry(namespace)
+                .or_default()
+                .push((rest.to_string(), class.clone())),
+            None if depth == 1 => {
+                ts.push_str(&format!("{}{} = new {}(this);\n", indent, path, class))
+            }
+            None => ts.push_str(&format!("{}{}: new {}(this),\n", indent, path, class)),
+        }
+    }
+    for (namespace, members) in namespaces {
+        let nested = ts_api_members(&members, depth + 1);
+        if depth == 1 {
+            ts.push_str(&format!(
+                "{}{} = {{\n{}{}}};\n",
+                indent, namespace, nested, indent
+            ));
+        } else {
+            ts.push_str(&format!(
+                "{}{}: {{\n{}{}}},\n",
+                indent, namespace, nested, indent
+            ));
+        }
+    }
+    ts
+}
+
+/// Print a unified diff of what the generation would change in `out_dir`, returns whether anything would.
+fn check(out_dir: &std::path::Path, files: &[(PathBuf, String)]) -> bool {
+    let manifest = Manifest::read(out_dir).unwrap_or_else(|| Manifest::new(&[]));
+    let sync = manifest.sync(out_dir, files, true);
+    let changes = files
+        .iter()
+        .filter(|(file, _)| sync.write.contains(file))
+        .map(|(file, content)| (file, content.as_str()))
+        .chain(sync.remove.iter().map(|file| (file, "")));
+    for (file, content) in changes {
+        let current = std::fs::read_to_string(out_dir.join(file)).unwrap_or_default();
+        let name = file.to_string_lossy().replace('\\', "/");
+        print!(
+            "{}",
+            similar::TextDiff::from_lines(current.as_str(), content)
+                .unified_diff()
+                .header(&format!("a/{}", name), &format!("b/{}", name))
+        );
+    }
+    !sync.write.is_empty() || !sync.remove.is_empty()
+}
+
+/// 小驼峰 转 大驼峰
+fn to_pascal_case(s: &str) -> String {
+    let camel = to_camel_case(s);
+    let mut chars = camel.chars();
+    match chars.next() {
+        Some(c) => c.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => camel,
+    }
+}
+
+/// 大驼峰 转 小驼峰
+fn to_camel_case(s: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize_next = false;
+
+    for (i, c) in s.chars().enumerate() {
+        if i == 0 {
+            result.push(c.to_ascii_lowercase());
+        } else if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            result.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+fn trans_to_ts_type(t: &str) -> &str {
+    match t {
+        "string" => "string",
+        "number" => "number",
+        "integer" => "number",
+        "boolean" => "boolean",
+        "array" => "any[]",
+        "object" => "any",
+        _ => "any",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jsdoc() {
+        let lines = vec![
+            "get a user".to_string(),
+            String::new(),
+            "ends with */ here".to_string(),
+            "@deprecated".to_string(),
+        ];
+        assert_eq!(
+            jsdoc(&lines, "  "),
+            "  /**\n   * get a user\n   *\n   * ends with *\\/ here\n   * @deprecated\n   */\n"
+        );
+        assert_eq!(jsdoc(&[], "  "), "");
+    }
+
+    #[test]
+    fn test_ts_doc() {
+        let mut builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/user/{x-id}": {
+                        "get": {
+                            "x-controller": "UserController",
+                            "x-router": "get_one",
+                            "deprecated": true,
+                            "parameters": [
+                                { "name": "x-id", "in": "path", "required": true, "description": "the user id", "example": 7, "schema": { "type": "integer" } },
+                                { "name": "page", "in": "query", "description": "page number", "schema": { "type": "integer", "default": 1 } }
+                            ]
+                        }
+                    }
+                }
+            }),
+        };
+        let doc = "  /**\n   * @param dto.page page number (default: 1)\n   * @param dto[\"x-id\"] the user id\n   * @deprecated\n   * @example\n   * await api.user.get_one({\"x-id\":7})\n   */\n  async get_one(";
+        assert!(builder.to_ts(ParamStyle::Flat).contains(doc));
+
+        builder.openapi["paths"]["/user/{x-id}"]["get"]["summary"] = json!("Get a user");
+        let doc = "  /**\n   * Get a user\n   *\n   * @param dto.query.page page number (default: 1)\n   * @param x_id the user id\n   * @deprecated\n   * @example\n   * await api.user.get_one(7)\n   */\n  async get_one(x_id: number, ";
+        assert!(builder.to_ts(ParamStyle::Positional).contains(doc));
+    }
+
+    #[test]
+    fn test_check() {
+        let dir = std::env::temp_dir().join(format!("nid-check-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("client")).unwrap();
+        let file = |name: &str, content: &str| (PathBuf::from(name), content.to_string());
+        let generated = vec![
+            file("client/a.ts", "a\n"),
+            file("client/b.ts", "b\n"),
+            file("client/c.ts", "c\n"),
+        ];
+        for (name, content) in &generated {
+            std::fs::write(dir.join(name), content).unwrap();
+        }
+        Manifest::new(&generated).write(&dir).unwrap();
+
+        assert!(!check(&dir, &generated));
+        let changed = vec![
+            file("client/a.ts", "a\n"),
+            file("client/b.ts", "b2\n"),
+            file("client/c.ts", "c\n"),
+        ];
+        assert!(check(&dir, &changed));
+        let mut added = generated.clone();
+        added.push(file("client/d.ts", "d\n"));
+        assert!(check(&dir, &added));
+        assert!(check(&dir, &generated[..2]));
+        // checking never touches the files
+        assert_eq!(
+            std::fs::read_to_string(dir.join("client/b.ts")).unwrap(),
+            "b\n"
+        );
+        assert!(dir.join("client/c.ts").exists());
+        assert!(!dir.join("client/d.ts").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_routes() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/v1/user/{id}": {
+                        "parameters": [
+                            { "name": "id", "in": "path", "required": true, "schema": { "type": "integer" } },
+                            { "name": "x-trace", "in": "header", "schema": { "type": "string" } }
+                        ],
+                        "get": {
+                            "x-controller": "UserController",
+                            "x-router": "get_one",
+                            "parameters": [
+                                { "name": "id", "in": "query", "schema": { "type": "string" } },
+                                { "name": "x-trace", "in": "header", "required": true, "schema": { "type": "string" } }
+                            ],
+                            "responses": { "200": { "content": { "application/json": { "schema": { "type": "object" } } } } }
+                        }
+                    }
+                }
+            }),
+        };
+        assert_eq!(
+            builder.to_routes(),
+            json!({
+                "version": ROUTES_VERSION,
+                "paths": {
+                    "/v1/user/{id}": {
+                        "get": {
+                            "params": { "path": ["id"], "query": ["id"], "header": ["x-trace"] },
+                            "accept": "application/json"
+                        }
+                    }
+                }
+            })
+        );
+
+        let ts = builder.to_ts(ParamStyle::Flat);
+        assert!(ts.contains("    ['path(id)']: number,\n"));
+        assert!(ts.contains("    ['query(id)']?: string,\n"));
+        assert!(ts.contains("    ['x-trace']: string,\n"));
+    }
+}
@@ -0,0 +1,265 @@
+use super::{
+    class_name,
+    naming::{ts_ident, ts_member},
+    schema::Stream,
+    to_camel_case, to_pascal_case, OpenapiBuilder,
+};
+
+impl OpenapiBuilder {
+    /// Mock Service Worker handlers per controller and operation, eg:
+    ///
+    /// ```ts
+    /// server.use(userControllerHandlers().get_one(UserControllerFixtures.get_one({ name: "wuma" })));
+    /// ```
+    pub fn to_msw(&self) -> String {
+        let controllers = self.controllers();
+        let mut ts = "// @ts-nocheck eslint-disable prettier-ignore\nimport { http, HttpResponse } from \"msw\";\n".to_string();
+        ts.push_str(&format!(
+            "import {{ {} }} from \"./fixtures\";\n\n",
+            controllers
+                .keys()
+                .map(|controller| fixtures_name(controller))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+
+        for (controller, operations) in &controllers {
+            ts.push_str(&format!(
+                "/* prettier-ignore */\nexport function {}(baseUrl = \"*\") {{\n  return {{\n",
+                handlers_name(controller)
+            ));
+            for opr in operations {
+                let (status, response) = self.success_response(opr.item);
+                // services merged with an absolute base url already carry their origin
+                let url = if opr.path.starts_with('/') {
+                    format!("${{baseUrl}}{}", msw_path(opr.path))
+                } else {
+                    msw_path(opr.path)
+                };
+                let reply = match (self.stream_media(opr.item), self.response_media(response)) {
+                    // the fixture of a stream is its items, framed the way `streamHandler` reads them
+                    (Some((stream, content_type, _)), _) => format!(
+                        "new HttpResponse(body.map((item) => {}).join(\"\"), {{ ...init, headers: {{ \"content-type\": \"{}\", ...init.headers }} }})",
+                        match stream {
+                            Stream::Sse => "`data: ${JSON.stringify(item)}\\n\\n`",
+                            Stream::Ndjson => "`${JSON.stringify(item)}\\n`",
+                        },
+                        content_type
+                    ),
+                    (None, Some((content_type, _))) if content_type.contains("json") => {
+                        "HttpResponse.json(body, init)".to_string()
+                    }
+                    (None, Some(_)) => "HttpResponse.text(String(body), init)".to_string(),
+                    (None, None) => "new HttpResponse(null, init)".to_string(),
+                };
+                ts.push_str(&format!(
+                    "    {router}: (body = {fixtures}.{router}(), init: ResponseInit = {{ status: {status} }}) =>\n      http.{method}(`{url}`, () => {reply}),\n",
+                    router = ts_member(opr.router),
+                    fixtures = fixtures_name(controller),
+                    method = opr.method,
+                ));
+            }
+            ts.push_str("  };\n}\n\n");
+        }
+
+        ts.push_str(
+            "/* prettier-ignore */\nexport function handlers(baseUrl = \"*\") {\n  return [\n",
+        );
+        for controller in controllers.keys() {
+            ts.push_str(&format!(
+                "    ...Object.values({}(baseUrl)).map((handler) => handler()),\n",
+                handlers_name(controller)
+            ));
+        }
+        ts.push_str("  ];\n}\n");
+
+        ts
+    }
+
+    /// Typed fixture factories derived from the response schemas, eg:
+    ///
+    /// ```ts
+    /// const user = UserControllerFixtures.get_one({ name: "wuma" });
+    /// ```
+    pub fn to_fixtures(&self) -> String {
+        let mut ts = "// @ts-nocheck eslint-disable prettier-ignore\n\n".to_string();
+
+        if let Some(schemas) = self.openapi["components"]["schemas"].as_object() {
+            for (name, schema) in schemas {
+                ts.push_str(&format!(
+                    "export type {} = {};\n",
+                    ts_ident(name),
+                    self.ts_type(schema)
+                ));
+            }
+            ts.push('\n');
+        }
+
+        for (controller, operations) in self.controllers() {
+            let mut factories = String::new();
+            for opr in operations {
+                let router = ts_member(opr.router);
+                let response_type = ts_ident(&format!(
+                    "{}{}Response",
+                    class_name(controller),
+                    to_pascal_case(opr.router)
+                ));
+                if let Some((_, _, item)) = self.stream_media(opr.item) {
+                    ts.push_str(&format!(
+                        "export type {} = Array<{}>;\n",
+                        response_type,
+                        self.ts_type(item)
+                    ));
+                    factories.push_str(&format!(
+                        "  {router}(items?: {t}): {t} {{\n    return items ?? ([{example}] as {t});\n  }},\n",
+                        router = router,
+                        t = response_type,
+                        example = self.sample(item),
+                    ));
+                    continue;
+                }
+                let (_, response) = self.success_response(opr.item);
+                let Some((_, media)) = self.response_media(response) else {
+                    ts.push_str(&format!("export type {} = void;\n", response_type));
+                    factories.push_str(&format!(
+                        "  {}(): {} {{\n    return undefined;\n  }},\n",
+                        router, response_type
+                    ));
+                    continue;
+                };
+
+                let example = self.media_example(media);
+                ts.push_str(&format!(
+                    "export type {} = {};\n",
+                    response_type,
+                    self.ts_type(&media["schema"])
+                ));
+                if example.is_object() {
+                    factories.push_str(&format!(
+                        "  {router}(overrides: Partial<{t}> = {{}}): {t} {{\n    return {{ ...{example}, ...overrides }} as {t};\n  }},\n",
+                        router = router,
+                        t = response_type,
+                    ));
+                } else {
+                    factories.push_str(&format!(
+                        "  {router}(value?: {t}): {t} {{\n    return value ?? ({example} as {t});\n  }},\n",
+                        router = router,
+                        t = response_type,
+                    ));
+                }
+            }
+            ts.push_str(&format!(
+                "\n/* prettier-ignore */\nexport const {} = {{\n{}}};\n\n",
+                fixtures_name(controller),
+                factories
+            ));
+        }
+
+        ts
+    }
+}
+
+fn handlers_name(controller: &str) -> String {
+    ts_ident(&format!(
+        "{}Handlers",
+        to_camel_case(&class_name(controller))
+    ))
+}
+
+fn fixtures_name(controller: &str) -> String {
+    ts_ident(&format!("{}Fixtures", class_name(controller)))
+}
+
+/// Convert an openapi path template to a msw path, eg: `/user/{id}` -> `/user/:id`.
+fn msw_path(path: &str) -> String {
+    path.split('/')
+        .map(
+            |segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(name) => format!(":{}", name),
+                None => segment.to_string(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_to_msw() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/user/{id}": {
+                        "get": {
+                            "x-controller": "UserController",
+                            "x-router": "get_one",
+                            "responses": {
+                                "200": {
+                                    "content": {
+                                        "application/json": {
+                                            "schema": {
+                                                "type": "object",
+                                                "required": ["id"],
+                                                "properties": { "id": { "type": "integer", "example": 1 } }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "/user/events": {
+                        "get": {
+                            "x-controller": "UserController",
+                            "x-router": "events",
+                            "responses": {
+                                "200": {
+                                    "content": {
+                                        "text/event-stream": {
+                                            "itemSchema": { "type": "object", "properties": { "id": { "type": "integer", "example": 1 } } }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "/user/export": {
+                        "get": {
+                            "x-controller": "UserController",
+                            "x-router": "export",
+                            "responses": {
+                                "200": { "content": { "application/x-ndjson": { "schema": { "type": "string", "example": "a" } } } }
+                            }
+                        }
+                    }
+                }
+            }),
+        };
+
+        let msw = builder.to_msw();
+        assert!(msw.contains("export function userControllerHandlers(baseUrl = \"*\")"));
+        assert!(
+            msw.contains("http.get(`${baseUrl}/user/:id`, () => HttpResponse.json(body, init))")
+        );
+
+        assert!(msw.contains("http.get(`${baseUrl}/user/events`, () => new HttpResponse(body.map((item) => `data: ${JSON.stringify(item)}\\n\\n`).join(\"\"), { ...init, headers: { \"content-type\": \"text/event-stream\", ...init.headers } }))"));
+        assert!(msw.contains("body.map((item) => `${JSON.stringify(item)}\\n`).join(\"\"), { ...init, headers: { \"content-type\": \"application/x-ndjson\""));
+
+        let fixtures = builder.to_fixtures();
+        assert!(
+            fixtures.contains("export type UserControllerEventsResponse = Array<{ id?: number }>;")
+        );
+        assert!(
+            fixtures.contains("return items ?? ([{\"id\":1}] as UserControllerEventsResponse);")
+        );
+        assert!(fixtures.contains("return items ?? ([\"a\"] as UserControllerExportResponse);"));
+        assert!(fixtures.contains("export type UserControllerGetOneResponse = { id: number };"));
+        assert!(fixtures
+            .contains("return { ...{\"id\":1}, ...overrides } as UserControllerGetOneResponse;"));
+    }
+}
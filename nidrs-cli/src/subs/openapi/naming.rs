@@ -0,0 +1,278 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{api_path, class_name, OpenapiBuilder};
+
+/// Words ts rejects as the name of a class, type, function or variable.
+#[rustfmt::skip]
+const TS_RESERVED: [&str; 52] = [
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do",
+    "else", "enum", "export", "extends", "false", "finally", "for", "function", "if", "import",
+    "in", "instanceof", "new", "null", "return", "super", "switch", "this", "throw", "true", "try",
+    "typeof", "var", "void", "while", "with", "yield", "let", "static", "implements", "interface",
+    "package", "private", "protected", "public", "await", "any", "boolean", "number", "string",
+    "symbol", "never",
+];
+
+/// Turn a name into a valid ts member name, eg: `get-one` -> `get_one`, `2fa` -> `_2fa`.
+///
+/// Reserved words are fine as members (`api.user.delete()`), see [`ts_ident`] for bindings.
+pub fn ts_member(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+/// Turn a name into a valid ts binding (class, type, function), eg: `default` -> `default_`.
+pub fn ts_ident(name: &str) -> String {
+    let ident = ts_member(name);
+    if TS_RESERVED.contains(&ident.as_str()) {
+        ident + "_"
+    } else {
+        ident
+    }
+}
+
+/// Quote a name as a ts single quoted string, eg: `it's` -> `'it\'s'`.
+pub fn ts_string(name: &str) -> String {
+    format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Quote the property key when it's not a plain identifier, eg: `user-id` -> `'user-id'`.
+pub fn ts_property_key(name: &str) -> String {
+    if ts_member(name) == name {
+        name.to_string()
+    } else {
+        ts_string(name)
+    }
+}
+
+/// Words rust rejects as a binding, `r#` escapes all but the path keywords.
+#[rustfmt::skip]
+const RUST_RESERVED: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "try",
+];
+
+/// Turn a name into a snake_case rust field or function, eg: `userName` -> `user_name`, `type` -> `r#type`.
+pub fn rust_ident(name: &str) -> String {
+    let mut ident = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                ident.push('_');
+            }
+            ident.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            ident.push(c);
+            prev_lower = true;
+        } else if !ident.ends_with('_') {
+            ident.push('_');
+            prev_lower = false;
+        }
+    }
+    let mut ident = ident.trim_matches('_').to_string();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    match ident.as_str() {
+        "self" | "super" | "crate" => ident + "_",
+        _ if RUST_RESERVED.contains(&ident.as_str()) => format!("r#{}", ident),
+        _ => ident,
+    }
+}
+
+/// Turn a name into a PascalCase rust type, eg: `user-dto` -> `UserDto`, `Self` -> `Self_`.
+pub fn rust_type(name: &str) -> String {
+    let mut ident = String::new();
+    let mut upper_next = true;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            ident.push(if upper_next {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            });
+            upper_next = false;
+        } else {
+            upper_next = true;
+        }
+    }
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if ident == "Self" {
+        ident.push('_');
+    }
+    ident
+}
+
+/// Types and functions the generated client declares or imports beside the classes and schemas.
+#[rustfmt::skip]
+const TS_GENERATED: [&str; 10] = [
+    "Api", "ApiOptions", "ApiAuth", "ApiEnv", "Credential", "reqHandler", "resHandler",
+    "streamHandler", "authHandler", "serverAdapt",
+];
+
+/// Members every generated controller class has, `constructor(private api: Api)`.
+const TS_CLASS_MEMBERS: [&str; 2] = ["constructor", "api"];
+
+/// The identifiers generated within one scope, with the names they were generated from.
+pub struct Scope {
+    name: String,
+    idents: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Scope {
+    pub fn new<K: Into<String>>(name: K) -> Self {
+        Self {
+            name: name.into(),
+            idents: BTreeMap::new(),
+        }
+    }
+
+    pub fn add<K: Into<String>>(&mut self, ident: String, source: K) {
+        self.idents.entry(ident).or_default().insert(source.into());
+    }
+
+    /// One line per identifier generated from more than one name.
+    pub fn collisions(&self) -> Vec<String> {
+        self.idents
+            .iter()
+            .filter(|(_, sources)| sources.len() > 1)
+            .map(|(ident, sources)| {
+                format!(
+                    "{}: `{}` <- {}",
+                    self.name,
+                    ident,
+                    sources.iter().cloned().collect::<Vec<_>>().join(", ")
+                )
+            })
+            .collect()
+    }
+}
+
+impl OpenapiBuilder {
+    /// Check that controllers, routers and schemas map to distinct ts identifiers,
+    /// and that none of them takes the name of something the client generates.
+    pub fn check_names(&self) -> Result<(), anyhow::Error> {
+        let mut scopes = vec![];
+
+        let mut api = Scope::new("Api");
+        // classes and schemas are declared side by side in the generated module
+        let mut types = Scope::new("types");
+        for name in TS_GENERATED {
+            types.add(name.to_string(), "generated");
+        }
+        for (controller, operations) in self.controllers() {
+            api.add(api_path(controller), controller);
+            types.add(class_name(controller), controller);
+
+            let mut routers = Scope::new(controller);
+            for member in TS_CLASS_MEMBERS {
+                routers.add(member.to_string(), "generated");
+            }
+            for opr in operations {
+                routers.add(
+                    ts_member(opr.router),
+                    format!("{} {}", opr.method.to_uppercase(), opr.path),
+                );
+            }
+            scopes.push(routers);
+        }
+        scopes.push(api);
+
+        if let Some(components) = self.openapi["components"]["schemas"].as_object() {
+            for name in components.keys() {
+                types.add(ts_ident(name), format!("#/components/schemas/{}", name));
+            }
+        }
+        scopes.push(types);
+
+        let collisions = scopes
+            .iter()
+            .flat_map(|scope| scope.collisions())
+            .collect::<Vec<_>>();
+        if collisions.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "naming collisions, rename them with `x-controller`/`x-router` or in the spec:\n  {}",
+                collisions.join("\n  ")
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_ts_ident() {
+        assert_eq!(ts_member("get-one"), "get_one");
+        assert_eq!(ts_member("2fa"), "_2fa");
+        assert_eq!(ts_member("delete"), "delete");
+        assert_eq!(ts_ident("default"), "default_");
+        assert_eq!(ts_ident("User.Controller"), "User_Controller");
+        assert_eq!(ts_property_key("user-id"), "'user-id'");
+        assert_eq!(ts_string("it's"), "'it\\'s'");
+    }
+
+    #[test]
+    fn test_rust_ident() {
+        assert_eq!(rust_ident("userName"), "user_name");
+        assert_eq!(rust_ident("get-one"), "get_one");
+        assert_eq!(rust_ident("type"), "r#type");
+        assert_eq!(rust_ident("self"), "self_");
+        assert_eq!(rust_ident("2fa"), "_2fa");
+        assert_eq!(rust_type("user-dto"), "UserDto");
+        assert_eq!(rust_type("billing.Invoice"), "BillingInvoice");
+    }
+
+    #[test]
+    fn test_check_names() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/a": { "get": { "x-controller": "UserController", "x-router": "get_one" } },
+                    "/b": { "get": { "x-controller": "User_Controller", "x-router": "get_one" } },
+                    "/c": { "get": { "x-controller": "UserController", "x-router": "get-one" } },
+                    "/d": { "post": { "x-controller": "UserController", "x-router": "constructor" } }
+                },
+                "components": {
+                    "schemas": {
+                        "UserController": { "type": "object" },
+                        "ApiOptions": { "type": "object" },
+                        "UserDto": { "type": "object" }
+                    }
+                }
+            }),
+        };
+        let err = builder.check_names().unwrap_err().to_string();
+        assert!(err.contains("UserController: `get_one` <- GET /a, GET /c"));
+        assert!(err.contains("UserController: `constructor` <- POST /d, generated"));
+        assert!(err.contains("Api: `user` <- UserController, User_Controller"));
+        assert!(err.contains(
+            "types: `UserController` <- #/components/schemas/UserController, UserController"
+        ));
+        assert!(err.contains("types: `ApiOptions` <- #/components/schemas/ApiOptions, generated"));
+        assert!(!err.contains("UserDto"));
+    }
+}
@@ -0,0 +1,494 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use crate::shared::exec_cmd;
+
+use super::{
+    api_path,
+    naming::{ts_member, ts_property_key, ts_string},
+    OpenapiBuilder,
+};
+
+/// Emit `out_dir` as a publishable npm package instead of a local api-client.
+#[derive(clap::Args, Debug)]
+pub struct PackageArgs {
+    /// emit a publishable package with a README, an `exports` map and a changelog.
+    #[clap(long)]
+    pub package: bool,
+
+    /// default: `info.title` of the spec
+    #[clap(long)]
+    pub package_name: Option<String>,
+
+    /// eg: --package-scope acme -> @acme/<name>
+    #[clap(long)]
+    pub package_scope: Option<String>,
+
+    /// default: `info.version` of the spec
+    #[clap(long)]
+    pub package_version: Option<String>,
+}
+
+/// eg: nid openapi pack ./packages/api-client --destination ./dist
+#[derive(clap::Parser, Debug)]
+pub struct Pack {
+    /// a package generated with `nid openapi --package`.
+    #[clap(default_value = "./node_modules/@nidist/api-client")]
+    out_dir: String,
+
+    /// where to write the tarball, default: the package directory
+    #[clap(short, long)]
+    destination: Option<String>,
+}
+
+impl Pack {
+    pub fn run(&self) {
+        let out_dir = PathBuf::from(&self.out_dir);
+        let pkg_json = std::fs::read_to_string(out_dir.join("package.json")).unwrap_or_default();
+        if !pkg_json.contains("\"prepack\"") {
+            eprintln!(
+                "[Pack] {:?} is not a package, generate it with `nid openapi --package` first.",
+                out_dir
+            );
+            std::process::exit(1);
+        }
+
+        if !out_dir.join("node_modules").exists() {
+            exec_cmd(
+                "Pack",
+                std::process::Command::new("npm")
+                    .arg("install")
+                    .current_dir(&out_dir),
+            )
+            .unwrap();
+        }
+
+        let destination = match &self.destination {
+            Some(destination) => {
+                std::fs::create_dir_all(destination).unwrap();
+                std::fs::canonicalize(destination).unwrap()
+            }
+            None => std::fs::canonicalize(&out_dir).unwrap(),
+        };
+        // npm pack runs `prepack`, which builds the package
+        if exec_cmd(
+            "Pack",
+            std::process::Command::new("npm")
+                .arg("pack")
+                .arg("--pack-destination")
+                .arg(&destination)
+                .current_dir(&out_dir),
+        )
+        .is_err()
+        {
+            std::process::exit(1);
+        }
+        println!("[Pack] tarball -> {:?}", destination);
+    }
+}
+
+impl PackageArgs {
+    /// The package name, eg: `info.title` "Nidrs Demo" -> `nidrs-demo`, with the scope `@acme/nidrs-demo`.
+    pub fn name(&self, builder: &OpenapiBuilder) -> String {
+        let name = self.package_name.clone().unwrap_or_else(|| {
+            let title = builder.openapi["info"]["title"]
+                .as_str()
+                .unwrap_or("api-client");
+            let slug = title
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        '-'
+                    }
+                })
+                .collect::<String>();
+            let slug = slug
+                .split('-')
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join("-");
+            if slug.is_empty() {
+                "api-client".to_string()
+            } else {
+                slug
+            }
+        });
+        match &self.package_scope {
+            Some(scope) if !name.starts_with('@') => {
+                format!("@{}/{}", scope.trim_start_matches('@'), name)
+            }
+            _ => name,
+        }
+    }
+
+    pub fn version(&self, builder: &OpenapiBuilder) -> String {
+        self.package_version
+            .clone()
+            .or_else(|| {
+                builder.openapi["info"]["version"]
+                    .as_str()
+                    .map(String::from)
+            })
+            .unwrap_or("0.0.0".to_string())
+    }
+
+    /// The package files around the generated `client/*.ts` modules.
+    ///
+    /// The changelog keeps the entries on disk and gets a new one per version, listing the added and removed operations.
+    pub fn files(
+        &self,
+        builder: &OpenapiBuilder,
+        files: &[(PathBuf, String)],
+        out_dir: &Path,
+    ) -> Vec<(PathBuf, String)> {
+        let name = self.name(builder);
+        let version = self.version(builder);
+        let modules = files
+            .iter()
+            .filter_map(|(file, _)| {
+                file.strip_prefix("client")
+                    .ok()?
+                    .to_str()?
+                    .strip_suffix(".ts")
+                    .map(String::from)
+            })
+            .collect::<Vec<_>>();
+        let index = files
+            .iter()
+            .find(|(file, _)| file.as_path() == Path::new("client/index.ts"))
+            .map(|(_, content)| content.as_str())
+            .unwrap_or_default();
+
+        // `types` must come first in the conditions of an export
+        let exports = modules
+            .iter()
+            .map(|module| {
+                let key = if module == "index" {
+                    ".".to_string()
+                } else {
+                    format!("./{}", module)
+                };
+                format!(
+                    "    \"{key}\": {{\n      \"types\": \"./dist/{module}.d.ts\",\n      \"import\": \"./dist/{module}.js\"\n    }}"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+
+        // the runtime the generated client asks for in its header
+        let runtime = index
+            .lines()
+            .find_map(|line| line.split_once("requires @nidrs/openapi-client-js >= "))
+            .map(|(_, version)| format!("^{}", version.trim()))
+            .unwrap_or_else(|| "^0.3.0".to_string());
+        let mut peers = Map::new();
+        for (module, peer, version) in [
+            ("msw", "msw", "^2.0.0"),
+            ("hooks", "@tanstack/react-query", "^5.0.0"),
+            ("hooks", "swr", "^2.0.0"),
+        ] {
+            let import = format!("from \"{}", peer);
+            let used = files.iter().any(|(file, content)| {
+                file.file_stem().and_then(|s| s.to_str()) == Some(module)
+                    && content.contains(&import)
+            });
+            if used {
+                peers.insert(peer.to_string(), json!(version));
+            }
+        }
+
+        // the conventional field order of package.json, serde_json sorts the keys of an object
+        let pretty = |value: Value| {
+            serde_json::to_string_pretty(&value)
+                .unwrap()
+                .replace('\n', "\n  ")
+        };
+        let mut fields = vec![
+            ("name", pretty(json!(name))),
+            ("version", pretty(json!(version))),
+            (
+                "description",
+                pretty(json!(builder.openapi["info"]["description"]
+                    .as_str()
+                    .unwrap_or("api client generated by nidrs-cli."))),
+            ),
+            ("type", pretty(json!("module"))),
+            ("main", pretty(json!("./dist/index.js"))),
+            ("types", pretty(json!("./dist/index.d.ts"))),
+            ("exports", format!("{{\n{}\n  }}", exports)),
+            (
+                "files",
+                pretty(json!(["dist", "README.md", "CHANGELOG.md"])),
+            ),
+            (
+                "scripts",
+                pretty(
+                    json!({ "build": "tsc -p tsconfig.build.json", "prepack": "npm run build" }),
+                ),
+            ),
+            (
+                "dependencies",
+                pretty(json!({ "@nidrs/openapi-client-js": runtime })),
+            ),
+        ];
+        if !peers.is_empty() {
+            fields.push(("peerDependencies", pretty(Value::Object(peers))));
+        }
+        fields.push(("devDependencies", pretty(json!({ "typescript": "^5.6.2" }))));
+        let pkg_json = fields
+            .iter()
+            .map(|(key, value)| format!("  \"{}\": {}", key, value))
+            .collect::<Vec<_>>()
+            .join(",\n");
+
+        let tsconfig = json!({
+            "compilerOptions": {
+                "target": "es2020",
+                "module": "esnext",
+                "moduleResolution": "bundler",
+                "lib": ["ESNext", "DOM"],
+                "declaration": true,
+                "skipLibCheck": true,
+                "rootDir": "./client",
+                "outDir": "./dist",
+            },
+            "include": ["client/**/*.ts"],
+        });
+
+        vec![
+            (
+                PathBuf::from("package.json"),
+                format!("{{\n{}\n}}\n", pkg_json),
+            ),
+            (
+                PathBuf::from("tsconfig.build.json"),
+                serde_json::to_string_pretty(&tsconfig).unwrap() + "\n",
+            ),
+            (
+                PathBuf::from("README.md"),
+                builder.to_readme(&name, &modules),
+            ),
+            (
+                PathBuf::from("CHANGELOG.md"),
+                changelog(builder, &version, index, out_dir),
+            ),
+        ]
+    }
+}
+
+impl OpenapiBuilder {
+    fn to_readme(&self, name: &str, modules: &[String]) -> String {
+        let title = self.openapi["info"]["title"].as_str().unwrap_or(name);
+        let mut md = format!("# {}\n\n", title);
+        if let Some(description) = self.openapi["info"]["description"].as_str() {
+            md.push_str(&format!("{}\n\n", description));
+        }
+        md.push_str("Generated by `nid openapi --package`, do not edit by hand.\n\n");
+        md.push_str(&format!(
+            "## Install\n\n```sh\nnpm install {} @nidrs/openapi-client-js\n```\n\n",
+            name
+        ));
+        let mut options = vec![];
+        if let Some((env, _)) = self.environments().last() {
+            options.push(format!("env: {}", ts_string(env)));
+        }
+        if let Some((scheme, _)) = self.security_schemes().first() {
+            options.push(format!(
+                "auth: {{ {}: () => token }}",
+                ts_property_key(scheme)
+            ));
+        }
+        let options = if options.is_empty() {
+            String::new()
+        } else {
+            format!(", {{ {} }}", options.join(", "))
+        };
+        md.push_str(&format!(
+            "## Usage\n\n```ts\nimport {{ fetchAdapt }} from \"@nidrs/openapi-client-js\";\nimport {{ Api }} from \"{}\";\n\nconst api = new Api(fetchAdapt(fetch){});\n```\n\n",
+            name, options
+        ));
+
+        let entries = modules
+            .iter()
+            .filter(|module| *module != "index")
+            .collect::<Vec<_>>();
+        if !entries.is_empty() {
+            md.push_str("## Entry points\n\n");
+            for module in entries {
+                md.push_str(&format!("- `{}/{}`\n", name, module));
+            }
+            md.push('\n');
+        }
+
+        md.push_str(
+            "## Operations\n\n| Call | Method | Path | Summary |\n| --- | --- | --- | --- |\n",
+        );
+        for (controller, operations) in self.controllers() {
+            for opr in operations {
+                md.push_str(&format!(
+                    "| `api.{}.{}()` | {} | `{}` | {} |\n",
+                    api_path(controller),
+                    ts_member(opr.router),
+                    opr.method.to_uppercase(),
+                    opr.path,
+                    opr.item["summary"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .replace('|', "\\|")
+                ));
+            }
+        }
+        md
+    }
+}
+
+/// Prepend an entry for `version` to the changelog on disk, diffing the operations of the previous client.
+fn changelog(builder: &OpenapiBuilder, version: &str, index: &str, out_dir: &Path) -> String {
+    let current = std::fs::read_to_string(out_dir.join("CHANGELOG.md")).unwrap_or_default();
+    let heading = format!("## {}\n", version);
+    if current.contains(&heading) {
+        return current;
+    }
+
+    let operations = |routes: &Value| {
+        let mut operations = vec![];
+        for (path, methods) in routes["paths"].as_object().into_iter().flatten() {
+            for method in methods.as_object().into_iter().flatten().map(|(m, _)| m) {
+                operations.push(format!("{} {}", method.to_uppercase(), path));
+            }
+        }
+        operations
+    };
+    let previous = std::fs::read_to_string(out_dir.join("client/index.ts"))
+        .ok()
+        .and_then(|ts| routes_of(&ts))
+        .map(|routes| operations(&routes));
+    let next = routes_of(index)
+        .map(|routes| operations(&routes))
+        .unwrap_or_else(|| operations(&builder.to_routes()));
+
+    let mut entry = heading;
+    match previous {
+        None => entry.push_str("\n- initial release\n"),
+        Some(previous) => {
+            let added = next.iter().filter(|opr| !previous.contains(opr));
+            let removed = previous.iter().filter(|opr| !next.contains(opr));
+            let mut lines = added
+                .map(|opr| format!("- added `{}`", opr))
+                .chain(removed.map(|opr| format!("- removed `{}`", opr)))
+                .collect::<Vec<_>>();
+            if lines.is_empty() {
+                lines.push("- regenerated from the spec".to_string());
+            }
+            entry.push_str(&format!("\n{}\n", lines.join("\n")));
+        }
+    }
+
+    let rest = current.trim_start_matches("# Changelog").trim_start();
+    if rest.is_empty() {
+        format!("# Changelog\n\n{}", entry)
+    } else {
+        format!("# Changelog\n\n{}\n{}", entry, rest)
+    }
+}
+
+/// The route table of a generated `client/index.ts`.
+fn routes_of(ts: &str) -> Option<Value> {
+    ts.lines()
+        .find_map(|line| line.trim().strip_prefix("routes = "))
+        .and_then(|routes| serde_json::from_str(routes.trim_end_matches(';')).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name() {
+        let builder = OpenapiBuilder {
+            openapi: json!({ "info": { "title": "Nidrs Demo API", "version": "1.2.0" } }),
+        };
+        let args = PackageArgs {
+            package: true,
+            package_name: None,
+            package_scope: Some("@acme".to_string()),
+            package_version: None,
+        };
+        assert_eq!(args.name(&builder), "@acme/nidrs-demo-api");
+        assert_eq!(args.version(&builder), "1.2.0");
+    }
+
+    #[test]
+    fn test_files() {
+        let builder = OpenapiBuilder {
+            openapi: json!({ "info": { "title": "Nidrs Demo API", "version": "1.2.0" } }),
+        };
+        let args = PackageArgs {
+            package: true,
+            package_name: None,
+            package_scope: Some("acme".to_string()),
+            package_version: None,
+        };
+        let index = "// routes v2, requires @nidrs/openapi-client-js >= 0.7.0\nexport class Api {\n  routes = {\"version\":2,\"paths\":{\"/user\":{\"get\":{}}}};\n}\n";
+        let files = vec![
+            (PathBuf::from("client/index.ts"), index.to_string()),
+            (
+                PathBuf::from("client/hooks.ts"),
+                "import { useQuery } from \"@tanstack/react-query\";\n".to_string(),
+            ),
+            (
+                PathBuf::from("client/msw.ts"),
+                "import { http, HttpResponse } from \"msw\";\n".to_string(),
+            ),
+        ];
+        let dir = std::env::temp_dir().join(format!("nid-package-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("client")).unwrap();
+        std::fs::write(
+            dir.join("client/index.ts"),
+            "  routes = {\"version\":2,\"paths\":{\"/user/{id}\":{\"delete\":{}}}};\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("CHANGELOG.md"),
+            "# Changelog\n\n## 1.1.0\n\n- initial release\n",
+        )
+        .unwrap();
+
+        let package = args.files(&builder, &files, &dir);
+        let file = |name: &str| {
+            package
+                .iter()
+                .find(|(file, _)| file.as_path() == Path::new(name))
+                .map(|(_, content)| content.clone())
+                .unwrap()
+        };
+
+        let pkg_json = file("package.json");
+        assert!(pkg_json
+            .starts_with("{\n  \"name\": \"@acme/nidrs-demo-api\",\n  \"version\": \"1.2.0\","));
+        let pkg: Value = serde_json::from_str(&pkg_json).unwrap();
+        assert_eq!(
+            pkg["exports"]["."],
+            json!({ "types": "./dist/index.d.ts", "import": "./dist/index.js" })
+        );
+        assert_eq!(pkg["exports"]["./hooks"]["import"], "./dist/hooks.js");
+        assert_eq!(pkg["dependencies"]["@nidrs/openapi-client-js"], "^0.7.0");
+        assert_eq!(
+            pkg["peerDependencies"],
+            json!({ "@tanstack/react-query": "^5.0.0", "msw": "^2.0.0" })
+        );
+
+        assert_eq!(
+            file("CHANGELOG.md"),
+            "# Changelog\n\n## 1.2.0\n\n- added `GET /user`\n- removed `DELETE /user/{id}`\n\n## 1.1.0\n\n- initial release\n"
+        );
+        assert!(
+            file("README.md").contains("npm install @acme/nidrs-demo-api @nidrs/openapi-client-js")
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use super::{jsdoc, naming::ts_ident, ts_property_key, OpenapiBuilder, Operation};
+
+/// How the generated methods take their parameters.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParamStyle {
+    /// one flat dto, names used in several locations are keyed as `'query(id)'`
+    #[default]
+    Flat,
+    /// `{ path: { id }, query: { page }, body }`
+    Structured,
+    /// path params as leading arguments, then `{ query, header, body }`
+    Positional,
+}
+
+/// The locations of a structured dto, in the order they are rendered.
+const LOCATIONS: [&str; 4] = ["path", "query", "header", "cookie"];
+
+impl ParamStyle {
+    /// The style argument of `reqHandler`, flat is its default.
+    pub fn handler_arg(&self) -> &'static str {
+        match self {
+            ParamStyle::Flat => "",
+            ParamStyle::Structured | ParamStyle::Positional => ", 'structured'",
+        }
+    }
+}
+
+/// The path params of a path template in order, eg: `/user/{id}/post/{post_id}` -> `id`, `post_id`.
+pub fn path_params(path: &str) -> Vec<&str> {
+    path.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+        .collect()
+}
+
+impl OpenapiBuilder {
+    /// The path params of an operation in the order of its path, one the spec does not declare is a string.
+    pub fn path_parameters(&self, opr: &Operation) -> Vec<Value> {
+        let parameters = self.parameters(opr);
+        path_params(opr.path)
+            .into_iter()
+            .map(|name| {
+                parameters
+                    .iter()
+                    .find(|param| param["in"] == "path" && param["name"] == name)
+                    .map(|param| (*param).clone())
+                    .unwrap_or_else(|| {
+                        json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } })
+                    })
+            })
+            .collect()
+    }
+
+    /// The parameter list of a method and the dto expression handed to `reqHandler`.
+    pub fn ts_args(
+        &self,
+        style: ParamStyle,
+        opr: &Operation,
+        flat_types: &str,
+    ) -> (String, String) {
+        if style == ParamStyle::Flat {
+            return (format!("dto:{} = {{}}", flat_types), "dto".to_string());
+        }
+
+        let mut groups = BTreeMap::<&str, Vec<Value>>::new();
+        for param in self.parameters(opr) {
+            match param["in"].as_str() {
+                Some("path") | None => {}
+                Some(location) => groups.entry(location).or_default().push(param.clone()),
+            }
+        }
+        let path = self.path_parameters(opr);
+        if !path.is_empty() {
+            groups.insert("path", path);
+        }
+
+        let mut args = vec![];
+        let mut members = vec![];
+        let mut required = false;
+        let mut positional = vec![];
+        for location in LOCATIONS {
+            let Some(keys) = groups.get(location) else {
+                continue;
+            };
+            if style == ParamStyle::Positional && location == "path" {
+                for key in keys {
+                    let name = key["name"].as_str().unwrap_or_default();
+                    args.push(format!(
+                        "{}: {}",
+                        ts_ident(name),
+                        self.ts_type(&key["schema"])
+                    ));
+                    positional.push(if ts_ident(name) == name {
+                        name.to_string()
+                    } else {
+                        format!("{}: {}", ts_property_key(name), ts_ident(name))
+                    });
+                }
+                continue;
+            }
+
+            let group_required = keys
+                .iter()
+                .any(|key| key["required"].as_bool().unwrap_or(false));
+            required |= group_required;
+            let mut group = "{\n".to_string();
+            for key in keys {
+                let name = key["name"].as_str().unwrap_or_default();
+                group.push_str(&jsdoc(&self.key_doc(key), "      "));
+                group.push_str(&format!(
+                    "      {}{}: {},\n",
+                    ts_property_key(name),
+                    if key["required"].as_bool().unwrap_or(false) {
+                        ""
+                    } else {
+                        "?"
+                    },
+                    self.ts_type(&key["schema"])
+                ));
+            }
+            group.push_str("    }");
+            members.push(format!(
+                "    {}{}: {},\n",
+                location,
+                if group_required { "" } else { "?" },
+                group
+            ));
+        }
+
+        let request_body = self.resolve(&opr.item["requestBody"]);
+        let schema = &request_body["content"]["application/json"]["schema"];
+        if schema.is_object() {
+            let body_required = request_body["required"].as_bool().unwrap_or(false);
+            required |= body_required;
+            members.push(format!(
+                "    body{}: {},\n",
+                if body_required { "" } else { "?" },
+                self.ts_type(schema)
+            ));
+        }
+
+        let dto_type = if members.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{\n{}  }}", members.concat())
+        };
+        args.push(if required {
+            format!("dto: {}", dto_type)
+        } else {
+            format!("dto: {} = {{}}", dto_type)
+        });
+
+        let dto = if positional.is_empty() {
+            "dto".to_string()
+        } else {
+            format!("{{ ...dto, path: {{ {} }} }}", positional.join(", "))
+        };
+        (args.join(", "), dto)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_ts_args() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/user/{id}": {
+                        "parameters": [
+                            { "name": "id", "in": "path", "required": true, "schema": { "type": "integer" } }
+                        ],
+                        "put": {
+                            "x-controller": "UserController",
+                            "x-router": "update",
+                            "parameters": [{ "name": "id", "in": "query", "schema": { "type": "integer" } }],
+                            "requestBody": {
+                                "required": true,
+                                "content": { "application/json": { "schema": { "type": "array", "items": { "type": "string" } } } }
+                            }
+                        }
+                    },
+                    "/user/{id}/post/{post-id}": {
+                        "get": {
+                            "x-controller": "UserController",
+                            "x-router": "get_post",
+                            "parameters": [{ "name": "id", "in": "path", "required": true, "schema": { "type": "integer" } }]
+                        }
+                    }
+                }
+            }),
+        };
+        let operations = builder.operations();
+        let update = operations
+            .iter()
+            .find(|opr| opr.router == "update")
+            .unwrap();
+        let get_post = operations
+            .iter()
+            .find(|opr| opr.router == "get_post")
+            .unwrap();
+
+        let (args, dto) = builder.ts_args(ParamStyle::Positional, update, "");
+        assert_eq!(
+            args,
+            "id: number, dto: {\n    query?: {\n      id?: number,\n    },\n    body: Array<string>,\n  }"
+        );
+        assert_eq!(dto, "{ ...dto, path: { id } }");
+
+        let (args, dto) = builder.ts_args(ParamStyle::Structured, update, "");
+        assert!(args.starts_with("dto: {\n    path: {\n      id: number,\n    },\n"));
+        assert_eq!(dto, "dto");
+        assert_eq!(path_params("/a/{b}/c/{d}"), vec!["b", "d"]);
+
+        // a path param the spec leaves out still gets an argument
+        let (args, dto) = builder.ts_args(ParamStyle::Positional, get_post, "");
+        assert_eq!(args, "id: number, post_id: string, dto: {} = {}");
+        assert_eq!(dto, "{ ...dto, path: { id, 'post-id': post_id } }");
+        let (args, _) = builder.ts_args(ParamStyle::Structured, get_post, "");
+        assert!(args.contains("      'post-id': string,\n"));
+        assert_eq!(
+            builder.to_routes()["paths"]["/user/{id}/post/{post-id}"]["get"]["params"],
+            json!({ "path": ["id", "post-id"] })
+        );
+        assert!(builder
+            .to_ts(ParamStyle::Flat)
+            .contains("    ['post-id']: string,\n"));
+
+        // `style` and cookie params of reqHandler came in 0.7.0
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": { "/user": { "get": { "x-controller": "UserController", "x-router": "get_all" } } }
+            }),
+        };
+        assert!(builder.to_ts(ParamStyle::Flat).contains(">= 0.3.0"));
+        assert!(builder.to_ts(ParamStyle::Structured).contains(">= 0.7.0"));
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": { "/user": { "get": {
+                    "x-controller": "UserController",
+                    "x-router": "get_all",
+                    "parameters": [{ "name": "session", "in": "cookie", "schema": { "type": "string" } }]
+                } } }
+            }),
+        };
+        assert!(builder.to_ts(ParamStyle::Flat).contains(">= 0.7.0"));
+    }
+}
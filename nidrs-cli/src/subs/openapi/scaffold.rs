@@ -0,0 +1,919 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use syn::ext::IdentExt;
+
+use super::{
+    class_name,
+    naming::{rust_ident, rust_type},
+    schema::schema_type,
+    OpenapiBuilder, HTTP_METHODS,
+};
+
+/// eg: nid openapi scaffold ./openapi.json --out ./src/modules
+#[derive(clap::Parser, Debug)]
+pub struct Scaffold {
+    /// nidrs server address or openapi spec file.
+    spec: String,
+
+    /// where the modules are generated.
+    #[clap(short, long, default_value = "./src/modules")]
+    out: String,
+
+    /// the `default_prefix` of the app, stripped from the paths, eg: /api/v1
+    #[clap(long)]
+    prefix: Option<String>,
+}
+
+/// An operation as a handler of a controller.
+struct Route<'a> {
+    method: &'a str,
+    /// the path of the spec
+    template: &'a str,
+    /// the path without the app prefix
+    path: String,
+    func: String,
+    item: &'a Value,
+}
+
+/// A nidrs module: one controller with its service and dtos.
+struct Module<'a> {
+    /// eg: `user`
+    name: String,
+    /// eg: `UserController`
+    controller: String,
+    routes: Vec<Route<'a>>,
+}
+
+impl Module<'_> {
+    fn service(&self) -> String {
+        format!("{}Service", rust_type(&self.name))
+    }
+
+    fn module(&self) -> String {
+        format!("{}Module", rust_type(&self.name))
+    }
+
+    /// The common literal prefix of the paths, eg: `/user` of `/user` and `/user/{id}`.
+    fn base_path(&self) -> String {
+        let paths = self
+            .routes
+            .iter()
+            .map(|route| route.path.split('/').filter(|s| !s.is_empty()).collect())
+            .collect::<Vec<Vec<_>>>();
+        let mut base = paths.first().cloned().unwrap_or_default();
+        for path in &paths {
+            let common = base
+                .iter()
+                .zip(path)
+                .take_while(|(a, b)| a == b && !a.starts_with('{'))
+                .count();
+            base.truncate(common);
+        }
+        let distinct = paths.iter().collect::<BTreeSet<_>>().len();
+        // a controller of one path keeps its last segment as the route, eg: `/app` + `/hello`
+        if distinct == 1 && base.len() > 1 {
+            base.pop();
+        }
+        format!("/{}", base.join("/"))
+    }
+}
+
+/// Rust code built from schemas, inline objects become structs named after where they are used.
+struct Codegen<'a> {
+    builder: &'a OpenapiBuilder,
+    /// component schemas referenced so far
+    refs: BTreeSet<String>,
+}
+
+impl Scaffold {
+    pub fn run(&self) {
+        let builder = match OpenapiBuilder::load(&self.spec) {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Openapi] {}", e);
+                std::process::exit(1);
+            }
+        };
+        let out = PathBuf::from(&self.out);
+        let modules = builder.scaffold_modules(self.prefix.as_deref().unwrap_or_default());
+        let mut codegen = Codegen {
+            builder: &builder,
+            refs: BTreeSet::new(),
+        };
+
+        let mut created = vec![];
+        for module in &modules {
+            let dir = out.join(&module.name);
+            if !dir.join("mod.rs").exists() {
+                created.push(module.module());
+            }
+            let base = module.base_path();
+            let mut dtos = BTreeMap::new();
+            let handlers = module
+                .routes
+                .iter()
+                .map(|route| (route.func.clone(), codegen.handler(route, &base, &mut dtos)))
+                .collect::<Vec<_>>();
+
+            write_new(&dir.join("mod.rs"), || module_rs(module));
+            write_new(&dir.join("service.rs"), || service_rs(module));
+            update_controller(&dir.join("controller.rs"), module, &base, &handlers);
+            update_items(
+                &dir.join("dto.rs"),
+                "pub use super::super::dto::*;\n",
+                &dtos,
+            );
+        }
+
+        // components reference each other, generate until no new one shows up
+        let mut shared = BTreeMap::new();
+        let mut done = BTreeSet::new();
+        while let Some(name) = codegen.refs.difference(&done).next().cloned() {
+            done.insert(name.clone());
+            let schema = &builder.openapi["components"]["schemas"][&name];
+            let code = codegen.item(&rust_type(&name), schema, &mut shared);
+            shared.insert(rust_type(&name), code);
+        }
+        update_items(&out.join("dto.rs"), "", &shared);
+
+        let mut mods = vec!["dto".to_string()];
+        mods.extend(modules.iter().map(|module| module.name.clone()));
+        update_mods(&out.join("mod.rs"), &mods);
+
+        if !created.is_empty() {
+            println!(
+                "[Scaffold] Add the new modules to the `imports` of your app module: {}",
+                created.join(", ")
+            );
+        }
+    }
+}
+
+impl OpenapiBuilder {
+    /// The operations grouped into modules by `x-controller`, else their first tag, else their first path segment.
+    fn scaffold_modules(&self, prefix: &str) -> Vec<Module<'_>> {
+        let mut modules = BTreeMap::<String, Module>::new();
+        let Some(paths) = self.openapi["paths"].as_object() else {
+            return vec![];
+        };
+        for (path, item) in paths {
+            let stripped = path
+                .strip_prefix(prefix.trim_end_matches('/'))
+                .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+                .unwrap_or(path);
+            let segment = stripped
+                .split('/')
+                .find(|s| !s.is_empty() && !s.starts_with('{'))
+                .unwrap_or("app");
+            for method in HTTP_METHODS {
+                let Some(opr) = item.get(method) else {
+                    continue;
+                };
+                let controller = match (opr["x-controller"].as_str(), opr["tags"][0].as_str()) {
+                    (Some(controller), _) => class_name(controller),
+                    (None, Some(tag)) => format!("{}Controller", rust_type(tag)),
+                    (None, None) => format!("{}Controller", rust_type(segment)),
+                };
+                let module = modules.entry(controller.clone()).or_insert_with(|| Module {
+                    name: rust_ident(controller.trim_end_matches("Controller")),
+                    controller,
+                    routes: vec![],
+                });
+                let router = opr["x-router"]
+                    .as_str()
+                    .or(opr["operationId"].as_str())
+                    .map(rust_ident)
+                    .unwrap_or_else(|| rust_ident(&format!("{} {}", method, stripped)));
+                let func = if module.routes.iter().any(|route| route.func == router) {
+                    format!("{}_{}", router, method)
+                } else {
+                    router
+                };
+                module.routes.push(Route {
+                    method,
+                    template: path,
+                    path: stripped.to_string(),
+                    func,
+                    item: opr,
+                });
+            }
+        }
+        modules.into_values().collect()
+    }
+}
+
+impl Codegen<'_> {
+    /// The handler of a route, its query and inline body/response structs go to `dtos`.
+    fn handler(
+        &mut self,
+        route: &Route,
+        base: &str,
+        dtos: &mut BTreeMap<String, String>,
+    ) -> String {
+        let builder = self.builder;
+        let pascal = rust_type(&route.func);
+        let mut code = String::new();
+        let summary = [&route.item["summary"], &route.item["description"]]
+            .iter()
+            .filter_map(|text| text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        code.push_str(&doc(&summary, "    "));
+        if route.item["deprecated"].as_bool().unwrap_or(false) {
+            code.push_str("    #[deprecated]\n");
+        }
+
+        let mut args = vec!["&self".to_string()];
+        let mut path_params = vec![];
+        let mut query = vec![];
+        let parameters = builder.openapi["paths"][route.template]["parameters"]
+            .as_array()
+            .into_iter()
+            .chain(route.item["parameters"].as_array())
+            .flatten()
+            .map(|param| builder.resolve(param));
+        for param in parameters {
+            match param["in"].as_str() {
+                Some("path") => path_params.push(param),
+                Some("query") => query.push(param),
+                _ => {}
+            }
+        }
+        // path params in the order of the path
+        path_params.sort_by_key(|param| {
+            route.path.find(&format!(
+                "{{{}}}",
+                param["name"].as_str().unwrap_or_default()
+            ))
+        });
+        match path_params.as_slice() {
+            [] => {}
+            [param] => {
+                let name = rust_ident(param["name"].as_str().unwrap_or_default());
+                let ty = self.rust_type(&param["schema"], "", dtos);
+                args.push(format!("Path({}): Path<{}>", name, ty));
+            }
+            params => {
+                let (names, types): (Vec<_>, Vec<_>) = params
+                    .iter()
+                    .map(|param| {
+                        (
+                            rust_ident(param["name"].as_str().unwrap_or_default()),
+                            self.rust_type(&param["schema"], "", dtos),
+                        )
+                    })
+                    .unzip();
+                args.push(format!(
+                    "Path(({})): Path<({})>",
+                    names.join(", "),
+                    types.join(", ")
+                ));
+            }
+        }
+        if !query.is_empty() {
+            let name = format!("{}Query", pascal);
+            let mut fields = String::new();
+            for param in query {
+                let required = param["required"].as_bool().unwrap_or(false);
+                fields.push_str(&self.field(
+                    param["name"].as_str().unwrap_or_default(),
+                    &param["schema"],
+                    param["description"].as_str().unwrap_or_default(),
+                    required,
+                    &name,
+                    dtos,
+                ));
+            }
+            dtos.insert(name.clone(), struct_code(&name, "", &fields));
+            args.push(format!("Query(query): Query<{}>", name));
+        }
+
+        let request_body = builder.resolve(&route.item["requestBody"]);
+        if let Some((content_type, media)) = builder.response_media(request_body) {
+            let ty = self.rust_type(&media["schema"], &format!("{}Body", pascal), dtos);
+            match content_type {
+                "application/json" => args.push(format!("Json(dto): Json<{}>", ty)),
+                "application/x-www-form-urlencoded" => {
+                    args.push(format!("Form(dto): Form<{}>", ty))
+                }
+                _ => {}
+            }
+        }
+
+        let (_, response) = builder.success_response(route.item);
+        let output = match builder.response_media(response) {
+            Some((content_type, media)) if content_type.contains("json") => format!(
+                "Json<{}>",
+                self.rust_type(&media["schema"], &format!("{}Response", pascal), dtos)
+            ),
+            Some((content_type, _)) if content_type.starts_with("text/") => "String".to_string(),
+            _ => "()".to_string(),
+        };
+
+        code.push_str(&format!(
+            "    #[{}(\"{}\")]\n",
+            route.method,
+            route_path(&route.path, base)
+        ));
+        code.push_str(&format!(
+            "    pub async fn {}({}) -> AppResult<{}> {{\n        todo!()\n    }}\n",
+            route.func,
+            args.join(", "),
+            output
+        ));
+        code
+    }
+
+    /// The rust type of a schema, `inline` names the struct generated for an inline object.
+    fn rust_type(
+        &mut self,
+        schema: &Value,
+        inline: &str,
+        structs: &mut BTreeMap<String, String>,
+    ) -> String {
+        if let Some(name) = schema["$ref"]
+            .as_str()
+            .and_then(|r| r.strip_prefix("#/components/schemas/"))
+        {
+            self.refs.insert(name.to_string());
+            return rust_type(name);
+        }
+        let schema = self.builder.resolve(schema);
+        if let Some([item]) = schema["allOf"].as_array().map(|items| items.as_slice()) {
+            return self.rust_type(item, inline, structs);
+        }
+        match schema_type(schema) {
+            "string" if schema["format"] == "binary" => "Vec<u8>".to_string(),
+            "string" => "String".to_string(),
+            "integer" if schema["format"] == "int32" => "i32".to_string(),
+            "integer" => "i64".to_string(),
+            "number" => "f64".to_string(),
+            "boolean" => "bool".to_string(),
+            "array" => format!(
+                "Vec<{}>",
+                self.rust_type(&schema["items"], &format!("{}Item", inline), structs)
+            ),
+            "object" if schema["properties"].is_object() && !inline.is_empty() => {
+                let code = self.item(inline, schema, structs);
+                structs.insert(inline.to_string(), code);
+                inline.to_string()
+            }
+            "object" if schema["additionalProperties"].is_object() => format!(
+                "std::collections::HashMap<String, {}>",
+                self.rust_type(&schema["additionalProperties"], "", structs)
+            ),
+            _ => "serde_json::Value".to_string(),
+        }
+    }
+
+    /// A struct, enum or type alias for a named schema.
+    fn item(
+        &mut self,
+        name: &str,
+        schema: &Value,
+        structs: &mut BTreeMap<String, String>,
+    ) -> String {
+        let description = schema["description"].as_str().unwrap_or_default();
+        if schema.get("$ref").is_some() {
+            let ty = self.rust_type(schema, "", structs);
+            return format!("{}pub type {} = {};\n", doc(description, ""), name, ty);
+        }
+        if let Some(values) = schema["enum"].as_array() {
+            if values.iter().all(|v| v.is_string()) {
+                let mut variants = String::new();
+                for value in values.iter().filter_map(|v| v.as_str()) {
+                    let variant = rust_type(value);
+                    if variant != value {
+                        variants.push_str(&format!("    #[serde(rename = \"{}\")]\n", value));
+                    }
+                    variants.push_str(&format!("    {},\n", variant));
+                }
+                return format!(
+                    "{}#[derive(Debug, Clone, Serialize, Deserialize)]\npub enum {} {{\n{}}}\n",
+                    doc(description, ""),
+                    name,
+                    variants
+                );
+            }
+        }
+
+        let mut fields = String::new();
+        let mut objects = vec![schema];
+        if let Some(all_of) = schema["allOf"].as_array() {
+            objects.clear();
+            for part in all_of {
+                match part["$ref"].as_str() {
+                    Some(_) => {
+                        let ty = self.rust_type(part, "", structs);
+                        fields.push_str(&format!(
+                            "    #[serde(flatten)]\n    pub {}: {},\n",
+                            rust_ident(&ty),
+                            ty
+                        ));
+                    }
+                    None => objects.push(self.builder.resolve(part)),
+                }
+            }
+        }
+        if objects
+            .iter()
+            .all(|object| !object["properties"].is_object())
+            && fields.is_empty()
+        {
+            let ty = match schema_type(schema) {
+                "object" => self.rust_type(schema, "", structs),
+                _ => self.rust_type(schema, name, structs),
+            };
+            return format!("{}pub type {} = {};\n", doc(description, ""), name, ty);
+        }
+        for object in objects {
+            let required = object["required"].as_array();
+            let Some(properties) = object["properties"].as_object() else {
+                continue;
+            };
+            for (property, schema) in properties {
+                let required = required.is_some_and(|r| r.iter().any(|r| r == property));
+                let description = schema["description"]
+                    .as_str()
+                    .or(self.builder.resolve(schema)["description"].as_str())
+                    .unwrap_or_default();
+                let inline = format!("{}{}", name, rust_type(property));
+                fields.push_str(&self.field(
+                    property,
+                    schema,
+                    description,
+                    required,
+                    &inline,
+                    structs,
+                ));
+            }
+        }
+        struct_code(name, description, &fields)
+    }
+
+    fn field(
+        &mut self,
+        name: &str,
+        schema: &Value,
+        description: &str,
+        required: bool,
+        inline: &str,
+        structs: &mut BTreeMap<String, String>,
+    ) -> String {
+        let ident = rust_ident(name);
+        let mut ty = self.rust_type(schema, inline, structs);
+        if !required || self.builder.resolve(schema)["nullable"] == true {
+            ty = format!("Option<{}>", ty);
+        }
+        let mut code = doc(description, "    ");
+        if ident.trim_start_matches("r#") != name {
+            code.push_str(&format!("    #[serde(rename = \"{}\")]\n", name));
+        }
+        code.push_str(&format!("    pub {}: {},\n", ident, ty));
+        code
+    }
+}
+
+/// A path relative to the controller path in axum syntax, eg: `/user/{id}` under `/user` -> `/:id`.
+fn route_path(path: &str, base: &str) -> String {
+    let route = path
+        .strip_prefix(base.trim_end_matches('/'))
+        .unwrap_or(path)
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(
+            |s| match s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(name) => format!(":{}", name),
+                None => s.to_string(),
+            },
+        )
+        .collect::<Vec<_>>();
+    format!("/{}", route.join("/"))
+}
+
+fn struct_code(name: &str, description: &str, fields: &str) -> String {
+    format!(
+        "{}#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct {} {{\n{}}}\n",
+        doc(description, ""),
+        name,
+        fields
+    )
+}
+
+fn doc(text: &str, indent: &str) -> String {
+    text.trim()
+        .lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                format!("{}///\n", indent)
+            } else {
+                format!("{}/// {}\n", indent, line.trim_end())
+            }
+        })
+        .collect()
+}
+
+fn module_rs(module: &Module) -> String {
+    format!(
+        "use nidrs::macros::module;
+
+pub mod controller;
+pub mod dto;
+pub mod service;
+
+use controller::{controller};
+use service::{service};
+
+#[module({{
+    controllers: [{controller}],
+    services: [{service}],
+    exports: [{service}],
+}})]
+pub struct {module};
+",
+        controller = module.controller,
+        service = module.service(),
+        module = module.module(),
+    )
+}
+
+fn service_rs(module: &Module) -> String {
+    format!(
+        "use nidrs::macros::injectable;
+
+#[injectable()]
+pub struct {service} {{}}
+
+impl {service} {{}}
+",
+        service = module.service()
+    )
+}
+
+/// The imports a handler needs, by the path they come from.
+fn imports(handlers: &[&str]) -> BTreeMap<&'static str, BTreeSet<&'static str>> {
+    let mut imports = BTreeMap::<&str, BTreeSet<&str>>::new();
+    for handler in handlers {
+        for (from, name, used) in [
+            ("nidrs::externs::axum::extract", "Path", "Path("),
+            ("nidrs::externs::axum::extract", "Query", "Query("),
+            ("nidrs::externs::axum", "Json", "Json<"),
+            ("nidrs::externs::axum", "Form", "Form("),
+        ] {
+            if handler.contains(used) {
+                imports.entry(from).or_default().insert(name);
+            }
+        }
+        for method in HTTP_METHODS {
+            if handler.contains(&format!("#[{}(", method)) {
+                imports.entry("nidrs::macros").or_default().insert(method);
+            }
+        }
+    }
+    imports
+}
+
+fn use_lines(imports: &BTreeMap<&str, BTreeSet<&str>>) -> String {
+    imports
+        .iter()
+        .filter(|(_, names)| !names.is_empty())
+        .map(|(from, names)| match names.len() {
+            1 => format!("use {}::{};\n", from, names.iter().next().unwrap()),
+            _ => format!(
+                "use {}::{{{}}};\n",
+                from,
+                names.iter().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        })
+        .collect()
+}
+
+/// Create the controller, or add the handlers of new operations to the one implemented so far.
+fn update_controller(file: &Path, module: &Module, base: &str, handlers: &[(String, String)]) {
+    let Ok(text) = std::fs::read_to_string(file) else {
+        let codes = handlers
+            .iter()
+            .map(|(_, code)| code.as_str())
+            .collect::<Vec<_>>();
+        let mut imports = imports(&codes);
+        imports
+            .entry("nidrs::macros")
+            .or_default()
+            .insert("controller");
+        imports
+            .entry("nidrs")
+            .or_default()
+            .extend(["AppResult", "Inject"]);
+        let code = format!(
+            "{}\nuse super::{{dto::*, service::{service}}};\n\n#[controller(\"{base}\")]\npub struct {controller} {{\n    {field}: Inject<{service}>,\n}}\n\nimpl {controller} {{\n{handlers}}}\n",
+            use_lines(&imports),
+            service = module.service(),
+            field = rust_ident(&module.service()),
+            controller = module.controller,
+            handlers = codes.join("\n"),
+        );
+        write(file, &code, "create");
+        return;
+    };
+
+    let file_ast = match syn::parse_file(&text) {
+        Ok(ast) => ast,
+        Err(e) => {
+            eprintln!("[Scaffold] Skip {:?}, it does not parse: {}", file, e);
+            return;
+        }
+    };
+    let mut existing = BTreeSet::new();
+    let mut used = BTreeSet::new();
+    for item in &file_ast.items {
+        match item {
+            syn::Item::Impl(item) if is_self_type(&item.self_ty, &module.controller) => {
+                for item in &item.items {
+                    if let syn::ImplItem::Fn(func) = item {
+                        existing.insert(func.sig.ident.unraw().to_string());
+                    }
+                }
+            }
+            syn::Item::Use(item) => use_names(&item.tree, &mut used),
+            _ => {}
+        }
+    }
+
+    let new = handlers
+        .iter()
+        .filter(|(func, _)| !existing.contains(func.trim_start_matches("r#")))
+        .collect::<Vec<_>>();
+    if new.is_empty() {
+        return;
+    }
+    let codes = new
+        .iter()
+        .map(|(_, code)| code.as_str())
+        .collect::<Vec<_>>();
+    let mut imports = imports(&codes);
+    for names in imports.values_mut() {
+        names.retain(|name| !used.contains(*name));
+    }
+
+    let additions = codes.join("\n");
+    let mut text = match impl_end(&text, &file_ast, &module.controller) {
+        Some(end) => format!("{}\n{}{}", &text[..end], additions, &text[end..]),
+        None => format!("{}\nimpl {} {{\n{}}}\n", text, module.controller, additions),
+    };
+    let uses = use_lines(&imports);
+    if !uses.is_empty() {
+        let at = last_use_end(&text);
+        text.insert_str(at, &uses);
+    }
+    write(
+        file,
+        &text,
+        &format!(
+            "update (+ {})",
+            new.iter()
+                .map(|(f, _)| f.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    );
+}
+
+fn is_self_type(ty: &syn::Type, name: &str) -> bool {
+    matches!(ty, syn::Type::Path(ty) if ty.path.segments.last().is_some_and(|s| s.ident == name))
+}
+
+fn use_names(tree: &syn::UseTree, names: &mut BTreeSet<String>) {
+    match tree {
+        syn::UseTree::Path(path) => use_names(&path.tree, names),
+        syn::UseTree::Name(name) => {
+            names.insert(name.ident.to_string());
+        }
+        syn::UseTree::Rename(rename) => {
+            names.insert(rename.rename.to_string());
+        }
+        syn::UseTree::Group(group) => group.items.iter().for_each(|tree| use_names(tree, names)),
+        syn::UseTree::Glob(_) => {}
+    }
+}
+
+/// The offset of the closing brace of `impl name { .. }`, taken from the span syn parsed it with.
+fn impl_end(text: &str, file: &syn::File, name: &str) -> Option<usize> {
+    let close = file.items.iter().find_map(|item| match item {
+        syn::Item::Impl(item) if item.trait_.is_none() && is_self_type(&item.self_ty, name) => {
+            Some(item.brace_token.span.close().start())
+        }
+        _ => None,
+    })?;
+    // lines count from 1 and columns in chars, the BOM stripped by `syn::parse_file` is not one
+    let body = text.trim_start_matches('\u{feff}');
+    let line_start: usize = body
+        .split_inclusive('\n')
+        .take(close.line - 1)
+        .map(str::len)
+        .sum();
+    let column: usize = body[line_start..]
+        .chars()
+        .take(close.column)
+        .map(char::len_utf8)
+        .sum();
+    Some(text.len() - body.len() + line_start + column)
+}
+
+/// The offset after the last top level `use` statement.
+fn last_use_end(text: &str) -> usize {
+    let mut end = 0;
+    let mut offset = 0;
+    let mut in_use = false;
+    for line in text.split_inclusive('\n') {
+        if line.starts_with("use ") || line.starts_with("pub use ") {
+            in_use = true;
+        }
+        offset += line.len();
+        if in_use && line.trim_end().ends_with(';') {
+            end = offset;
+            in_use = false;
+        }
+    }
+    end
+}
+
+const SERDE_USE: &str = "use serde::{Deserialize, Serialize};\n";
+
+/// Create the file, or append the structs it does not declare yet.
+fn update_items(file: &Path, header: &str, items: &BTreeMap<String, String>) {
+    let Ok(text) = std::fs::read_to_string(file) else {
+        if items.is_empty() && header.is_empty() {
+            return;
+        }
+        let mut parts = vec![];
+        if !items.is_empty() {
+            parts.push(SERDE_USE);
+        }
+        if !header.is_empty() {
+            parts.push(header);
+        }
+        parts.extend(items.values().map(|item| item.as_str()));
+        write(file, &parts.join("\n"), "create");
+        return;
+    };
+    let declared = match syn::parse_file(&text) {
+        Ok(ast) => ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Struct(item) => Some(item.ident.to_string()),
+                syn::Item::Enum(item) => Some(item.ident.to_string()),
+                syn::Item::Type(item) => Some(item.ident.to_string()),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>(),
+        Err(e) => {
+            eprintln!("[Scaffold] Skip {:?}, it does not parse: {}", file, e);
+            return;
+        }
+    };
+    let new = items
+        .iter()
+        .filter(|(name, _)| !declared.contains(*name))
+        .collect::<Vec<_>>();
+    if new.is_empty() {
+        return;
+    }
+    let mut text = text;
+    if !text.contains("Serialize") {
+        text.insert_str(0, SERDE_USE);
+    }
+    for (_, code) in &new {
+        text.push('\n');
+        text.push_str(code);
+    }
+    write(
+        file,
+        &text,
+        &format!(
+            "update (+ {})",
+            new.iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    );
+}
+
+/// Create `mod.rs` of the modules dir, or declare the modules it misses.
+fn update_mods(file: &Path, mods: &[String]) {
+    let text = std::fs::read_to_string(file).unwrap_or_default();
+    let new = mods
+        .iter()
+        .filter(|name| !text.contains(&format!("mod {};", name)))
+        .collect::<Vec<_>>();
+    if new.is_empty() {
+        return;
+    }
+    let action = if text.is_empty() { "create" } else { "update" };
+    let mut text = text;
+    for name in new {
+        text.push_str(&format!("pub mod {};\n", name));
+    }
+    write(file, &text, action);
+}
+
+fn write_new(file: &Path, content: impl FnOnce() -> String) {
+    if !file.exists() {
+        write(file, &content(), "create");
+    }
+}
+
+fn write(file: &Path, content: &str, action: &str) {
+    if let Some(parent) = file.parent() {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            eprintln!("[Scaffold] create {:?}: {}", parent, e);
+            std::process::exit(1);
+        }
+    }
+    if let Err(e) = std::fs::write(file, content) {
+        eprintln!("[Scaffold] write {:?}: {}", file, e);
+        std::process::exit(1);
+    }
+    println!("[Scaffold] {} {:?}", action, file);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_scaffold() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/api/user/{id}": {
+                        "get": {
+                            "tags": ["user"],
+                            "operationId": "getOne",
+                            "parameters": [
+                                { "name": "id", "in": "path", "required": true, "schema": { "type": "integer" } },
+                                { "name": "fields", "in": "query", "schema": { "type": "string" } }
+                            ],
+                            "responses": { "200": { "content": { "application/json": {
+                                "schema": { "$ref": "#/components/schemas/UserDto" }
+                            } } } }
+                        }
+                    },
+                    "/api/user": { "post": { "tags": ["user"], "responses": {} } },
+                    "/api/user/type": { "get": { "tags": ["user"], "operationId": "type", "responses": {} } }
+                },
+                "components": { "schemas": { "UserDto": { "type": "object" } } }
+            }),
+        };
+        let modules = builder.scaffold_modules("/api");
+        assert_eq!(modules.len(), 1);
+        let module = &modules[0];
+        assert_eq!(module.controller, "UserController");
+        assert_eq!(module.base_path(), "/user");
+
+        let mut codegen = Codegen {
+            builder: &builder,
+            refs: BTreeSet::new(),
+        };
+        let mut dtos = BTreeMap::new();
+        let get_one = module.routes.iter().find(|r| r.func == "get_one").unwrap();
+        assert_eq!(
+            codegen.handler(get_one, "/user", &mut dtos),
+            "    #[get(\"/:id\")]\n    pub async fn get_one(&self, Path(id): Path<i64>, Query(query): Query<GetOneQuery>) -> AppResult<Json<UserDto>> {\n        todo!()\n    }\n"
+        );
+        assert!(dtos["GetOneQuery"].contains("pub fields: Option<String>,"));
+        assert!(codegen.refs.contains("UserDto"));
+        assert_eq!(module.routes[0].func, "post_user");
+
+        // a rerun leaves the handlers already there, raw identifiers included
+        let base = module.base_path();
+        let handlers = module
+            .routes
+            .iter()
+            .map(|route| (route.func.clone(), codegen.handler(route, &base, &mut dtos)))
+            .collect::<Vec<_>>();
+        assert!(handlers.iter().any(|(func, _)| func == "r#type"));
+        let dir = std::env::temp_dir().join(format!("nid-scaffold-{}", std::process::id()));
+        let file = dir.join("controller.rs");
+        update_controller(&file, module, &base, &handlers);
+        let created = std::fs::read_to_string(&file).unwrap();
+        update_controller(&file, module, &base, &handlers);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), created);
+        assert_eq!(created.matches("pub async fn r#type").count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+
+        let text = "impl UserController {\n    fn a() { \"}\" }\n    // }\n}\n";
+        let ast = syn::parse_file(text).unwrap();
+        assert_eq!(impl_end(text, &ast, "UserController"), Some(text.len() - 2));
+        let text = "\u{feff}#!/usr/bin/env run\nimpl Default for UserController {}\nimpl UserController {\n    fn a() -> char { '{' } // ü\n}\n";
+        let ast = syn::parse_file(text).unwrap();
+        assert_eq!(impl_end(text, &ast, "UserController"), Some(text.len() - 2));
+    }
+}
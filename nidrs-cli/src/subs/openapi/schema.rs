@@ -0,0 +1,458 @@
+use serde_json::{json, Map, Value};
+
+use super::OpenapiBuilder;
+
+/// Guards `$ref` cycles and self referencing schemas.
+const MAX_DEPTH: usize = 8;
+
+static NULL: Value = Value::Null;
+
+/// How a streaming response frames its items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    /// `text/event-stream`, one item per `data:` event
+    Sse,
+    /// newline delimited json, one item per line
+    Ndjson,
+}
+
+impl Stream {
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type.split(';').next().unwrap_or_default().trim() {
+            "text/event-stream" => Some(Stream::Sse),
+            "application/x-ndjson"
+            | "application/ndjson"
+            | "application/jsonl"
+            | "application/x-jsonlines"
+            | "application/stream+json" => Some(Stream::Ndjson),
+            _ => None,
+        }
+    }
+
+    /// The format argument of `streamHandler` in `@nidrs/openapi-client-js`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stream::Sse => "sse",
+            Stream::Ndjson => "ndjson",
+        }
+    }
+}
+
+impl OpenapiBuilder {
+    /// Follow local `$ref`s, eg: `#/components/schemas/UserDto`.
+    pub fn resolve<'a>(&'a self, schema: &'a Value) -> &'a Value {
+        let mut schema = schema;
+        for _ in 0..MAX_DEPTH {
+            let Some(r) = schema["$ref"].as_str() else {
+                return schema;
+            };
+            schema = r
+                .strip_prefix('#')
+                .and_then(|pointer| self.openapi.pointer(pointer))
+                .unwrap_or(&NULL);
+        }
+        schema
+    }
+
+    /// The first documented 2xx response of the operation, falling back to `default`.
+    pub fn success_response<'a>(&'a self, opr: &'a Value) -> (u16, &'a Value) {
+        let success = opr["responses"].as_object().and_then(|responses| {
+            responses
+                .iter()
+                .filter(|(code, _)| code.starts_with('2'))
+                .min_by_key(|(code, _)| code.as_str())
+        });
+        match success {
+            Some((code, response)) => (code.parse().unwrap_or(200), self.resolve(response)),
+            None => (200, self.resolve(&opr["responses"]["default"])),
+        }
+    }
+
+    /// The streaming success response of the operation, with its content type and the schema of each item.
+    ///
+    /// The item schema is `itemSchema` when the spec has one, else the schema of the media.
+    pub fn stream_media<'a>(&'a self, opr: &'a Value) -> Option<(Stream, &'a str, &'a Value)> {
+        let (_, response) = self.success_response(opr);
+        response["content"]
+            .as_object()?
+            .iter()
+            .find_map(|(content_type, media)| {
+                let stream = Stream::from_content_type(content_type)?;
+                let schema = match media.get("itemSchema") {
+                    Some(schema) => schema,
+                    None => &media["schema"],
+                };
+                Some((stream, content_type.as_str(), schema))
+            })
+    }
+
+    /// The content type and media object of a response, json is preferred.
+    pub fn response_media<'a>(&'a self, response: &'a Value) -> Option<(&'a str, &'a Value)> {
+        let content = self.resolve(response)["content"].as_object()?;
+        content
+            .get_key_value("application/json")
+            .or_else(|| content.iter().next())
+            .map(|(content_type, media)| (content_type.as_str(), media))
+    }
+
+    /// The example of a media object, synthesized from its schema when the spec has none.
+    pub fn media_example(&self, media: &Value) -> Value {
+        media
+            .get("example")
+            .cloned()
+            .or_else(|| {
+                media["examples"].as_object().and_then(|examples| {
+                    examples
+                        .values()
+                        .next()
+                        .map(|example| self.resolve(example)["value"].clone())
+                })
+            })
+            .unwrap_or_else(|| self.sample(&media["schema"]))
+    }
+
+    /// Build a sample value of the schema, preferring the examples and defaults of the spec.
+    pub fn sample(&self, schema: &Value) -> Value {
+        self.sample_depth(schema, 0)
+    }
+
+    fn sample_depth(&self, schema: &Value, depth: usize) -> Value {
+        let schema = self.resolve(schema);
+
+        if let Some(example) = schema.get("example") {
+            return example.clone();
+        }
+        if let Some(example) = schema["examples"].as_array().and_then(|e| e.first()) {
+            return example.clone();
+        }
+        if let Some(default) = schema.get("default") {
+            return default.clone();
+        }
+        if let Some(value) = schema.get("const") {
+            return value.clone();
+        }
+        if let Some(value) = schema["enum"].as_array().and_then(|e| e.first()) {
+            return value.clone();
+        }
+        if depth > MAX_DEPTH {
+            return Value::Null;
+        }
+        if let Some(all_of) = schema["allOf"].as_array() {
+            let mut merged = Map::new();
+            for item in all_of {
+                if let Value::Object(obj) = self.sample_depth(item, depth + 1) {
+                    merged.extend(obj);
+                }
+            }
+            return Value::Object(merged);
+        }
+        if let Some(first) = schema["oneOf"]
+            .as_array()
+            .or_else(|| schema["anyOf"].as_array())
+            .and_then(|items| items.first())
+        {
+            return self.sample_depth(first, depth + 1);
+        }
+
+        match schema_type(schema) {
+            "string" => match schema["format"].as_str().unwrap_or_default() {
+                "date-time" => json!("2024-01-01T00:00:00Z"),
+                "date" => json!("2024-01-01"),
+                "time" => json!("00:00:00"),
+                "email" => json!("user@example.com"),
+                "uuid" => json!("00000000-0000-0000-0000-000000000000"),
+                "uri" | "url" => json!("https://example.com"),
+                "binary" | "byte" => json!(""),
+                _ => json!("string"),
+            },
+            "integer" => json!(schema["minimum"].as_i64().unwrap_or(0)),
+            "number" => json!(schema["minimum"].as_f64().unwrap_or(0.0)),
+            "boolean" => json!(true),
+            "array" => json!([self.sample_depth(&schema["items"], depth + 1)]),
+            "object" => {
+                let mut obj = Map::new();
+                if let Some(properties) = schema["properties"].as_object() {
+                    for (name, property) in properties {
+                        obj.insert(name.clone(), self.sample_depth(property, depth + 1));
+                    }
+                }
+                Value::Object(obj)
+            }
+            _ => Value::Null,
+        }
+    }
+
+    /// Validate the value against the schema, errors are reported with their json path, eg: `$.user.name`.
+    pub fn validate(&self, schema: &Value, value: &Value) -> Vec<String> {
+        let mut errors = vec![];
+        self.validate_at(schema, value, "$", 0, &mut errors);
+        errors
+    }
+
+    fn validate_at(
+        &self,
+        schema: &Value,
+        value: &Value,
+        path: &str,
+        depth: usize,
+        errors: &mut Vec<String>,
+    ) {
+        let schema = self.resolve(schema);
+        if depth > MAX_DEPTH || !schema.is_object() {
+            return;
+        }
+
+        if let Some(all_of) = schema["allOf"].as_array() {
+            for item in all_of {
+                self.validate_at(item, value, path, depth + 1, errors);
+            }
+        }
+        if let Some(any_of) = schema["oneOf"]
+            .as_array()
+            .or_else(|| schema["anyOf"].as_array())
+        {
+            let matched = any_of.iter().any(|item| {
+                let mut errs = vec![];
+                self.validate_at(item, value, path, depth + 1, &mut errs);
+                errs.is_empty()
+            });
+            if !matched {
+                errors.push(format!(
+                    "{}: does not match any of the allowed schemas",
+                    path
+                ));
+            }
+        }
+
+        if value.is_null() && is_nullable(schema) {
+            return;
+        }
+
+        if let Some(values) = schema["enum"].as_array() {
+            if !values.contains(value) {
+                errors.push(format!(
+                    "{}: {} is not one of {}",
+                    path,
+                    value,
+                    Value::from(values.clone())
+                ));
+            }
+        }
+
+        let expected = schema_type(schema);
+        let actual = value_type(value);
+        let type_matched = match expected {
+            "" => true,
+            "number" => actual == "number" || actual == "integer",
+            _ => expected == actual,
+        };
+        if !type_matched {
+            errors.push(format!("{}: expected {}, got {}", path, expected, actual));
+            return;
+        }
+
+        match value {
+            Value::Object(obj) => {
+                if let Some(required) = schema["required"].as_array() {
+                    for name in required.iter().filter_map(|r| r.as_str()) {
+                        if !obj.contains_key(name) {
+                            errors.push(format!("{}.{}: is required", path, name));
+                        }
+                    }
+                }
+                if let Some(properties) = schema["properties"].as_object() {
+                    for (name, property) in properties {
+                        if let Some(v) = obj.get(name) {
+                            let p = format!("{}.{}", path, name);
+                            self.validate_at(property, v, &p, depth + 1, errors);
+                        }
+                    }
+                }
+            }
+            Value::Array(items) if schema.get("items").is_some() => {
+                for (i, item) in items.iter().enumerate() {
+                    let p = format!("{}[{}]", path, i);
+                    self.validate_at(&schema["items"], item, &p, depth + 1, errors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Convert a raw path/query/header parameter to the json value described by its schema.
+    pub fn coerce(&self, schema: &Value, raw: &str) -> Result<Value, String> {
+        let schema = self.resolve(schema);
+        match schema_type(schema) {
+            "integer" => raw
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| format!("expected integer, got {:?}", raw)),
+            "number" => raw
+                .parse::<f64>()
+                .map(Value::from)
+                .map_err(|_| format!("expected number, got {:?}", raw)),
+            "boolean" => raw
+                .parse::<bool>()
+                .map(Value::from)
+                .map_err(|_| format!("expected boolean, got {:?}", raw)),
+            "array" => raw
+                .split(',')
+                .map(|item| self.coerce(&schema["items"], item))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::from),
+            _ => Ok(Value::from(raw)),
+        }
+    }
+}
+
+/// The json type of the schema, `""` when the schema accepts anything.
+pub fn schema_type(schema: &Value) -> &str {
+    match &schema["type"] {
+        Value::String(t) => t,
+        Value::Array(types) => types
+            .iter()
+            .filter_map(|t| t.as_str())
+            .find(|t| *t != "null")
+            .unwrap_or("null"),
+        _ => {
+            if schema.get("properties").is_some() {
+                "object"
+            } else if schema.get("items").is_some() {
+                "array"
+            } else {
+                ""
+            }
+        }
+    }
+}
+
+fn is_nullable(schema: &Value) -> bool {
+    schema["nullable"].as_bool().unwrap_or(false)
+        || schema["type"]
+            .as_array()
+            .map(|types| types.iter().any(|t| t == "null"))
+            .unwrap_or(false)
+}
+
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> OpenapiBuilder {
+        OpenapiBuilder {
+            openapi: json!({
+                "components": {
+                    "schemas": {
+                        "UserDto": {
+                            "type": "object",
+                            "required": ["id", "name"],
+                            "properties": {
+                                "id": { "type": "integer" },
+                                "name": { "type": "string", "example": "wuma" },
+                                "email": { "type": "string", "format": "email" },
+                                "tags": { "type": "array", "items": { "type": "string" } },
+                                "parent": { "$ref": "#/components/schemas/UserDto" }
+                            }
+                        }
+                    }
+                }
+            }),
+        }
+    }
+
+    #[test]
+    fn test_sample() {
+        let builder = builder();
+        let value = builder.sample(&json!({ "$ref": "#/components/schemas/UserDto" }));
+        assert_eq!(value["id"], json!(0));
+        assert_eq!(value["name"], json!("wuma"));
+        assert_eq!(value["email"], json!("user@example.com"));
+        assert_eq!(value["tags"], json!(["string"]));
+        assert!(value["parent"].is_object());
+    }
+
+    #[test]
+    fn test_validate() {
+        let builder = builder();
+        let schema = json!({ "$ref": "#/components/schemas/UserDto" });
+        assert!(builder
+            .validate(&schema, &json!({ "id": 1, "name": "wuma" }))
+            .is_empty());
+        assert_eq!(
+            builder.validate(&schema, &json!({ "id": "1", "tags": ["a", 2] })),
+            vec![
+                "$.name: is required",
+                "$.id: expected integer, got string",
+                "$.tags[1]: expected string, got integer",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coerce() {
+        let builder = OpenapiBuilder {
+            openapi: json!({ "components": { "schemas": { "PageNo": { "type": "integer" } } } }),
+        };
+        assert_eq!(
+            builder.coerce(&json!({ "type": "integer" }), "12"),
+            Ok(json!(12))
+        );
+        assert!(builder
+            .coerce(&json!({ "type": "integer" }), "abc")
+            .is_err());
+        assert_eq!(
+            builder.coerce(
+                &json!({ "type": "array", "items": { "type": "boolean" } }),
+                "true,false"
+            ),
+            Ok(json!([true, false]))
+        );
+        assert_eq!(
+            builder.coerce(
+                &json!({ "type": "array", "items": { "$ref": "#/components/schemas/PageNo" } }),
+                "1,2"
+            ),
+            Ok(json!([1, 2]))
+        );
+    }
+
+    #[test]
+    fn test_stream_media() {
+        let builder = builder();
+        let opr = json!({
+            "responses": {
+                "200": {
+                    "content": {
+                        "application/x-ndjson": { "itemSchema": { "type": "string" } },
+                        "text/event-stream": { "schema": { "type": "integer" } }
+                    }
+                }
+            }
+        });
+        let (stream, content_type, item) = builder.stream_media(&opr).unwrap();
+        assert_eq!(stream, Stream::Ndjson);
+        assert_eq!(content_type, "application/x-ndjson");
+        assert_eq!(item, &json!({ "type": "string" }));
+        assert_eq!(
+            Stream::from_content_type("text/event-stream; charset=utf-8"),
+            Some(Stream::Sse)
+        );
+        assert!(builder
+            .stream_media(
+                &json!({ "responses": { "200": { "content": { "application/json": {} } } } })
+            )
+            .is_none());
+    }
+}
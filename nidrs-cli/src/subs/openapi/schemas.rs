@@ -0,0 +1,371 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use super::OpenapiBuilder;
+
+/// The dialect of the written schemas.
+pub const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The keywords holding a map of subschemas.
+const SCHEMA_MAPS: [&str; 5] = [
+    "properties",
+    "patternProperties",
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+];
+
+/// The keywords holding a list of subschemas.
+const SCHEMA_LISTS: [&str; 4] = ["allOf", "anyOf", "oneOf", "prefixItems"];
+
+/// The keywords holding one subschema.
+const SCHEMA_ONES: [&str; 11] = [
+    "items",
+    "additionalProperties",
+    "not",
+    "contains",
+    "propertyNames",
+    "if",
+    "then",
+    "else",
+    "unevaluatedProperties",
+    "unevaluatedItems",
+    "additionalItems",
+];
+
+/// openapi keywords json schema does not know.
+const OPENAPI_ONLY: [&str; 4] = ["nullable", "discriminator", "xml", "externalDocs"];
+
+/// eg: nid openapi schemas ./openapi.json --out ./schemas
+#[derive(clap::Parser, Debug)]
+pub struct Schemas {
+    /// nidrs server address, openapi spec file or nidrs project dir.
+    #[clap(default_value = "http://localhost:3000")]
+    spec: String,
+
+    #[clap(short, long, default_value = "./schemas")]
+    out: String,
+}
+
+impl Schemas {
+    pub fn run(&self) {
+        let builder = match OpenapiBuilder::load(&self.spec) {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Schemas] {}", e);
+                std::process::exit(1);
+            }
+        };
+        let out_dir = PathBuf::from(&self.out);
+        let files = builder.to_json_schemas();
+
+        // schemas of a previous run which are gone from the spec
+        for dir in ["components", "operations"] {
+            let Ok(entries) = std::fs::read_dir(out_dir.join(dir)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let file = path.strip_prefix(&out_dir).unwrap_or(&path).to_path_buf();
+                if is_schema_file(&path) && !files.iter().any(|(f, _)| *f == file) {
+                    println!("[Schemas] remove {}", file.display());
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+
+        for (file, schema) in &files {
+            let path = out_dir.join(file);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            std::fs::write(&path, serde_json::to_string_pretty(schema).unwrap() + "\n").unwrap();
+        }
+
+        println!("[Schemas] {} schemas -> {:?}", files.len(), out_dir);
+    }
+}
+
+impl OpenapiBuilder {
+    /// One Draft 2020-12 schema per component schema, and per request and response body of the operations.
+    ///
+    /// eg: `components/UserDto.schema.json`, `operations/UserController.create.request.schema.json`
+    pub fn to_json_schemas(&self) -> Vec<(PathBuf, Value)> {
+        let mut files = vec![];
+
+        if let Some(schemas) = self.openapi["components"]["schemas"].as_object() {
+            for (name, schema) in schemas {
+                let schema = self.json_schema(schema, "");
+                files.push((
+                    PathBuf::from("components").join(schema_file(name)),
+                    document(schema, name, None),
+                ));
+            }
+        }
+
+        for opr in self.operations() {
+            let name = format!("{}.{}", opr.controller, opr.router);
+
+            let request_body = self.resolve(&opr.item["requestBody"]);
+            if let Some((_, media)) = self.response_media(request_body) {
+                if media["schema"].is_object() {
+                    files.push((
+                        PathBuf::from("operations").join(schema_file(&format!("{}.request", name))),
+                        document(
+                            self.json_schema(&media["schema"], "../components/"),
+                            &format!("{} request body", name),
+                            request_body["description"].as_str(),
+                        ),
+                    ));
+                }
+            }
+
+            let (_, response) = self.success_response(opr.item);
+            let (kind, schema) = match self.stream_media(opr.item) {
+                Some((_, _, item)) => ("item", item),
+                None => match self.response_media(response) {
+                    Some((_, media)) => ("response", &media["schema"]),
+                    None => continue,
+                },
+            };
+            if schema.is_object() {
+                files.push((
+                    PathBuf::from("operations").join(schema_file(&format!("{}.{}", name, kind))),
+                    document(
+                        self.json_schema(schema, "../components/"),
+                        &format!(
+                            "{} {}",
+                            name,
+                            if kind == "item" {
+                                "stream item"
+                            } else {
+                                "response body"
+                            }
+                        ),
+                        response["description"].as_str(),
+                    ),
+                ));
+            }
+        }
+
+        files
+    }
+
+    /// Convert an openapi schema to Draft 2020-12, component `$ref`s point to the files in `base`.
+    fn json_schema(&self, schema: &Value, base: &str) -> Value {
+        let Some(object) = schema.as_object() else {
+            return schema.clone();
+        };
+
+        let mut converted = Map::new();
+        for (key, value) in object {
+            match key.as_str() {
+                "$ref" => match value
+                    .as_str()
+                    .and_then(|r| r.strip_prefix("#/components/schemas/"))
+                {
+                    Some(rest) => {
+                        let (name, pointer) = match rest.split_once('/') {
+                            Some((name, pointer)) => (name, format!("#/{}", pointer)),
+                            None => (rest, String::new()),
+                        };
+                        let name = name.replace("~1", "/").replace("~0", "~");
+                        converted.insert(
+                            key.clone(),
+                            json!(format!("{}{}{}", base, schema_file(&name), pointer)),
+                        );
+                    }
+                    // refs out of the component schemas have no file, inline them
+                    None => {
+                        if let Value::Object(inlined) = self.json_schema(self.resolve(schema), base)
+                        {
+                            converted.extend(inlined);
+                        }
+                    }
+                },
+                "example" if !object.contains_key("examples") => {
+                    converted.insert("examples".to_string(), json!([value]));
+                }
+                "example" => {}
+                "exclusiveMinimum" | "exclusiveMaximum" if value.is_boolean() => {
+                    let bound = if key == "exclusiveMinimum" {
+                        "minimum"
+                    } else {
+                        "maximum"
+                    };
+                    if value == true {
+                        if let Some(limit) = object.get(bound) {
+                            converted.insert(key.clone(), limit.clone());
+                        }
+                    }
+                }
+                key if OPENAPI_ONLY.contains(&key) => {}
+                key if SCHEMA_MAPS.contains(&key) => {
+                    let schemas = value
+                        .as_object()
+                        .into_iter()
+                        .flatten()
+                        .map(|(name, schema)| (name.clone(), self.json_schema(schema, base)))
+                        .collect();
+                    converted.insert(key.to_string(), Value::Object(schemas));
+                }
+                key if SCHEMA_LISTS.contains(&key) => {
+                    let schemas = value
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|schema| self.json_schema(schema, base))
+                        .collect();
+                    converted.insert(key.to_string(), Value::Array(schemas));
+                }
+                key if SCHEMA_ONES.contains(&key) => {
+                    converted.insert(key.to_string(), self.json_schema(value, base));
+                }
+                _ => {
+                    converted.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        // an openapi 3.0 exclusive bound replaces the inclusive one
+        for (exclusive, bound) in [
+            ("exclusiveMinimum", "minimum"),
+            ("exclusiveMaximum", "maximum"),
+        ] {
+            if object.get(exclusive) == Some(&json!(true)) {
+                converted.remove(bound);
+            }
+        }
+
+        if object.get("nullable") != Some(&json!(true)) {
+            return Value::Object(converted);
+        }
+        match converted.get("type").cloned() {
+            Some(Value::String(t)) => {
+                converted.insert("type".to_string(), json!([t, "null"]));
+                if let Some(Value::Array(values)) = converted.get_mut("enum") {
+                    if !values.contains(&Value::Null) {
+                        values.push(Value::Null);
+                    }
+                }
+                Value::Object(converted)
+            }
+            _ => json!({ "anyOf": [converted, { "type": "null" }] }),
+        }
+    }
+}
+
+/// A schema file with its dialect and a title.
+fn document(schema: Value, title: &str, description: Option<&str>) -> Value {
+    let mut document = Map::new();
+    document.insert("$schema".to_string(), json!(DRAFT_2020_12));
+    document.insert("title".to_string(), json!(title));
+    if let Some(description) = description {
+        document.insert("description".to_string(), json!(description));
+    }
+    match schema {
+        Value::Object(schema) => document.extend(schema),
+        Value::Bool(false) => {
+            document.insert("not".to_string(), json!({}));
+        }
+        _ => {}
+    }
+    Value::Object(document)
+}
+
+/// The file of a schema, chars unsafe in paths and uris are replaced, eg: `Page<User>` -> `Page_User_.schema.json`.
+fn schema_file(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}.schema.json", name)
+}
+
+fn is_schema_file(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|path| path.ends_with(".schema.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_schemas() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "paths": {
+                    "/user": {
+                        "post": {
+                            "x-controller": "UserController",
+                            "x-router": "create",
+                            "requestBody": {
+                                "content": { "application/json": { "schema": { "$ref": "#/components/schemas/UserDto" } } }
+                            },
+                            "responses": {
+                                "201": {
+                                    "description": "created",
+                                    "content": { "application/json": { "schema": { "type": "array", "items": { "$ref": "#/components/schemas/UserDto" } } } }
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "UserDto": {
+                            "type": "object",
+                            "properties": {
+                                "name": { "type": "string", "nullable": true, "example": "wuma" },
+                                "age": { "type": "integer", "minimum": 0, "exclusiveMinimum": true },
+                                "parent": { "$ref": "#/components/schemas/UserDto", "nullable": true }
+                            }
+                        }
+                    }
+                }
+            }),
+        };
+
+        let files = builder.to_json_schemas();
+        let paths = files
+            .iter()
+            .map(|(file, _)| file.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "components/UserDto.schema.json",
+                "operations/UserController.create.request.schema.json",
+                "operations/UserController.create.response.schema.json",
+            ]
+        );
+
+        let user = &files[0].1;
+        assert_eq!(user["$schema"], DRAFT_2020_12);
+        assert_eq!(
+            user["properties"]["name"],
+            json!({ "type": ["string", "null"], "examples": ["wuma"] })
+        );
+        assert_eq!(
+            user["properties"]["age"],
+            json!({ "type": "integer", "exclusiveMinimum": 0 })
+        );
+        assert_eq!(
+            user["properties"]["parent"],
+            json!({ "anyOf": [{ "$ref": "UserDto.schema.json" }, { "type": "null" }] })
+        );
+        assert_eq!(files[1].1["$ref"], "../components/UserDto.schema.json");
+        assert_eq!(files[2].1["description"], "created");
+        assert_eq!(
+            files[2].1["items"]["$ref"],
+            "../components/UserDto.schema.json"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use serde_json::{Map, Value};
+
+use super::{jsdoc, naming::ts_property_key, OpenapiBuilder};
+
+impl OpenapiBuilder {
+    /// The security alternatives of an operation, each one the scheme names sent together.
+    ///
+    /// The operation's `security` overrides the document's, no alternative means a public operation
+    /// and an empty one (`{}`) is dropped as it only makes the credentials optional.
+    pub fn security<'a>(&'a self, opr: &'a Value) -> Vec<Vec<&'a str>> {
+        let security = opr.get("security").unwrap_or(&self.openapi["security"]);
+        security
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|requirement| requirement.as_object())
+            .map(|requirement| requirement.keys().map(|name| name.as_str()).collect())
+            .filter(|names: &Vec<&str>| !names.is_empty())
+            .collect()
+    }
+
+    /// Whether an operation sends credentials only if they are configured.
+    fn optional_security(&self, opr: &Value) -> bool {
+        let security = opr.get("security").unwrap_or(&self.openapi["security"]);
+        security
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|requirement| requirement.as_object().is_some_and(|r| r.is_empty()))
+    }
+
+    /// The declared security schemes, `$ref`s resolved.
+    pub fn security_schemes(&self) -> Vec<(&str, &Value)> {
+        self.openapi["components"]["securitySchemes"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(name, scheme)| (name.as_str(), self.resolve(scheme)))
+            .collect()
+    }
+
+    /// The schemes of the route table, only what `authHandler` needs to place a credential.
+    pub fn to_route_schemes(&self) -> Option<Value> {
+        let schemes = self.security_schemes();
+        if schemes.is_empty() {
+            return None;
+        }
+        let mut map = Map::new();
+        for (name, scheme) in schemes {
+            let mut route = Map::new();
+            for key in ["type", "scheme", "in", "name"] {
+                if let Some(value) = scheme.get(key) {
+                    route.insert(key.to_string(), value.clone());
+                }
+            }
+            map.insert(name.to_string(), Value::Object(route));
+        }
+        Some(Value::Object(map))
+    }
+
+    /// The `ApiAuth` interface taken by the `Api` constructor, one credential per scheme.
+    pub fn ts_auth(&self) -> String {
+        let mut ts = "/* prettier-ignore */\nexport interface ApiAuth {\n".to_string();
+        for (name, scheme) in self.security_schemes() {
+            let mut lines = vec![];
+            if let Some(description) = scheme["description"].as_str() {
+                lines.extend(description.lines().map(|l| l.to_string()));
+            }
+            lines.push(scheme_doc(scheme));
+            ts.push_str(&jsdoc(&lines, "  "));
+
+            let credential = if scheme["type"] == "http"
+                && scheme["scheme"].as_str().map(|s| s.to_lowercase()) == Some("basic".to_string())
+            {
+                "Credential<{ username: string; password: string }>"
+            } else {
+                "Credential"
+            };
+            ts.push_str(&format!("  {}?: {};\n", ts_property_key(name), credential));
+        }
+        ts.push_str("}\n");
+        ts
+    }
+
+    /// The doc line telling which credentials an operation sends.
+    pub fn security_doc(&self, opr: &Value) -> String {
+        let security = self.security(opr);
+        if security.is_empty() {
+            return "Public, no credentials are sent.".to_string();
+        }
+        let alternatives = security
+            .iter()
+            .map(|names| names.join(" + "))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        let scopes = self.scopes(opr);
+        let scopes = if scopes.is_empty() {
+            String::new()
+        } else {
+            format!(" (scopes: {})", scopes.join(", "))
+        };
+        if self.optional_security(opr) {
+            format!("Auth (optional): {}{}", alternatives, scopes)
+        } else {
+            format!("Auth: {}{}", alternatives, scopes)
+        }
+    }
+
+    /// The scopes an operation asks for, across its alternatives.
+    fn scopes<'a>(&'a self, opr: &'a Value) -> Vec<&'a str> {
+        let security = opr.get("security").unwrap_or(&self.openapi["security"]);
+        let mut scopes = vec![];
+        for requirement in security.as_array().into_iter().flatten() {
+            for scope in requirement
+                .as_object()
+                .into_iter()
+                .flatten()
+                .flat_map(|(_, scopes)| scopes.as_array().into_iter().flatten())
+                .filter_map(|scope| scope.as_str())
+            {
+                if !scopes.contains(&scope) {
+                    scopes.push(scope);
+                }
+            }
+        }
+        scopes
+    }
+}
+
+/// How a scheme sends its credential, eg: "API key, sent in the `X-API-Key` header."
+fn scheme_doc(scheme: &Value) -> String {
+    let name = scheme["name"].as_str().unwrap_or_default();
+    match scheme["type"].as_str().unwrap_or_default() {
+        "apiKey" => match scheme["in"].as_str().unwrap_or("header") {
+            "query" => format!("API key, sent as the `{}` query param.", name),
+            "cookie" => format!("API key, sent as the `{}` cookie.", name),
+            _ => format!("API key, sent in the `{}` header.", name),
+        },
+        "http" => match scheme["scheme"].as_str().unwrap_or("bearer") {
+            s if s.eq_ignore_ascii_case("basic") => {
+                "Username and password, sent as `Authorization: Basic`.".to_string()
+            }
+            s if s.eq_ignore_ascii_case("bearer") => match scheme["bearerFormat"].as_str() {
+                Some(format) => format!("{} token, sent as `Authorization: Bearer`.", format),
+                None => "Token, sent as `Authorization: Bearer`.".to_string(),
+            },
+            s => format!("Credential, sent as `Authorization: {}`.", s),
+        },
+        "oauth2" => {
+            let flows = scheme["flows"]
+                .as_object()
+                .map(|flows| flows.keys().cloned().collect::<Vec<_>>().join(", "))
+                .unwrap_or_default();
+            let flows = if flows.is_empty() {
+                String::new()
+            } else {
+                format!(" ({} flow)", flows)
+            };
+            format!(
+                "OAuth2 access token{}, sent as `Authorization: Bearer`.",
+                flows
+            )
+        }
+        "openIdConnect" => {
+            "OpenID Connect access token, sent as `Authorization: Bearer`.".to_string()
+        }
+        other => format!("{} credential.", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_security() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "security": [{ "bearerAuth": [] }],
+                "components": {
+                    "securitySchemes": {
+                        "bearerAuth": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                        "apiKey": { "type": "apiKey", "in": "query", "name": "key", "description": "From the console" },
+                        "oauth": { "type": "oauth2", "flows": { "implicit": { "scopes": {} } } }
+                    }
+                }
+            }),
+        };
+
+        assert_eq!(builder.security(&json!({})), vec![vec!["bearerAuth"]]);
+        assert!(builder.security(&json!({ "security": [] })).is_empty());
+        assert_eq!(
+            builder.security_doc(&json!({ "security": [{ "oauth": ["read"] }, {}] })),
+            "Auth (optional): oauth (scopes: read)"
+        );
+        assert_eq!(
+            builder.security_doc(&json!({ "security": [] })),
+            "Public, no credentials are sent."
+        );
+        assert_eq!(
+            builder.to_route_schemes().unwrap()["apiKey"],
+            json!({ "type": "apiKey", "in": "query", "name": "key" })
+        );
+
+        let ts = builder.ts_auth();
+        assert!(
+            ts.contains("   * From the console\n   * API key, sent as the `key` query param.\n")
+        );
+        assert!(ts.contains("  bearerAuth?: Credential;\n"));
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use serde_json::{json, Value};
+
+use super::{servers::server_url, OpenapiBuilder};
+
+/// The explorer page, self-contained so the spec can be browsed offline.
+const EXPLORER_HTML: &str = include_str!("assets/explorer.html");
+
+/// The prefix of the "try it out" requests sent to `--proxy`.
+const PROXY_PREFIX: &str = "/__nid/proxy";
+
+/// The dirs the watcher does not walk into.
+const UNWATCHED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// eg: nid openapi serve ./openapi.yaml --port 4100 --proxy http://localhost:3000
+#[derive(clap::Parser, Debug)]
+pub struct Serve {
+    /// openapi spec file, nidrs project dir or nidrs server address.
+    spec: String,
+
+    #[clap(short, long, default_value = "4100")]
+    port: u16,
+
+    /// the address to listen on, `0.0.0.0` opens it to the other machines of the network.
+    #[clap(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// send the "try it out" requests through the explorer to this backend,
+    /// an url or the env of a server of the spec, eg: `http://localhost:3000`, `staging`.
+    #[clap(long)]
+    proxy: Option<String>,
+}
+
+/// The spec as last loaded, `version` goes up on each reload so the page knows to refetch it.
+struct Explorer {
+    version: u64,
+    spec: Value,
+    error: Option<String>,
+}
+
+impl Serve {
+    pub fn run(&self) {
+        let builder = match OpenapiBuilder::load(&self.spec) {
+            Ok(builder) => builder,
+            Err(e) => {
+                eprintln!("[Serve] {}", e);
+                std::process::exit(1);
+            }
+        };
+        let backend = match &self.proxy {
+            Some(proxy) => match proxy_backend(&builder, proxy) {
+                Some(backend) => Some(backend),
+                None => {
+                    eprintln!(
+                        "[Serve] --proxy `{}` is neither an url nor an env of the spec servers",
+                        proxy
+                    );
+                    std::process::exit(1);
+                }
+            },
+            None => None,
+        };
+
+        let explorer = Arc::new(Mutex::new(Explorer {
+            version: 1,
+            spec: builder.openapi,
+            error: None,
+        }));
+        self.watch(explorer.clone());
+
+        let server = match tiny_http::Server::http((self.host.as_str(), self.port)) {
+            Ok(server) => server,
+            Err(e) => {
+                eprintln!("[Serve] listen on {}:{}: {}", self.host, self.port, e);
+                std::process::exit(1);
+            }
+        };
+        println!("[Serve] explorer on http://{}:{}", self.host, self.port);
+        if let Some(backend) = &backend {
+            println!("[Serve] try it out -> {}", backend);
+        }
+
+        for request in server.incoming_requests() {
+            let explorer = explorer.clone();
+            let backend = backend.clone();
+            // a proxied request may take a while, it must not hold up the page
+            std::thread::spawn(move || handle(request, &explorer, backend.as_deref()));
+        }
+    }
+
+    /// Reload the spec when a file it is read from changes, a spec from a server is not watched.
+    fn watch(&self, explorer: Arc<Mutex<Explorer>>) {
+        let Some((root, extensions)) = watched(&self.spec) else {
+            return;
+        };
+        let source = self.spec.clone();
+        std::thread::spawn(move || {
+            let mut last = fingerprint(&root, extensions);
+            loop {
+                std::thread::sleep(Duration::from_millis(500));
+                let current = fingerprint(&root, extensions);
+                if current == last {
+                    continue;
+                }
+                last = current;
+                let loaded = OpenapiBuilder::load(&source);
+                let mut explorer = explorer.lock().unwrap();
+                explorer.version += 1;
+                match loaded {
+                    Ok(builder) => {
+                        println!(
+                            "[Serve] reloaded, {} operations",
+                            builder.operations().len()
+                        );
+                        explorer.spec = builder.openapi;
+                        explorer.error = None;
+                    }
+                    // the page keeps the last good spec and shows the error
+                    Err(e) => {
+                        eprintln!("[Serve] reload failed: {}", e);
+                        explorer.error = Some(e.to_string());
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// The url of `--proxy`, given as an url or as the env of a server of the spec.
+fn proxy_backend(builder: &OpenapiBuilder, proxy: &str) -> Option<String> {
+    if proxy.starts_with("http://") || proxy.starts_with("https://") {
+        return Some(proxy.trim_end_matches('/').to_string());
+    }
+    let (_, server) = builder
+        .environments()
+        .into_iter()
+        .find(|(env, _)| env == proxy)?;
+    Some(server_url(server))
+}
+
+/// The dir to watch for a spec and the extensions of the files it is read from.
+fn watched(source: &str) -> Option<(PathBuf, &'static [&'static str])> {
+    let path = Path::new(source);
+    if path.is_dir() {
+        return Some((path.to_path_buf(), &["rs", "toml"]));
+    }
+    if !path.is_file() {
+        return None;
+    }
+    // external `$ref`s are mostly beside the spec or below it
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    Some((dir, &["json", "yaml", "yml"]))
+}
+
+/// The modification time of each watched file, it changes when a file is edited, added or removed.
+fn fingerprint(root: &Path, extensions: &[&str]) -> Vec<(PathBuf, SystemTime)> {
+    let mut files = vec![];
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                if !name.starts_with('.') && !UNWATCHED_DIRS.contains(&name.as_str()) {
+                    dirs.push(path);
+                }
+            } else if path
+                .extension()
+                .is_some_and(|ext| extensions.contains(&ext.to_string_lossy().as_ref()))
+            {
+                let modified = entry
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                files.push((path, modified));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// The backend url of a proxied request, eg: `/__nid/proxy/user/1?page=2` -> `http://localhost:3000/user/1?page=2`.
+fn proxy_url(backend: &str, url: &str) -> Option<String> {
+    let rest = url.strip_prefix(PROXY_PREFIX)?;
+    if !(rest.is_empty() || rest.starts_with('/') || rest.starts_with('?')) {
+        return None;
+    }
+    Some(format!("{}{}", backend, rest))
+}
+
+fn handle(mut request: tiny_http::Request, explorer: &Mutex<Explorer>, backend: Option<&str>) {
+    let url = request.url().to_string();
+    let path = url.split('?').next().unwrap_or_default();
+
+    let response = if let Some(target) = backend.and_then(|backend| proxy_url(backend, &url)) {
+        let method = request.method().to_string();
+        let response = proxy(&mut request, &target).unwrap_or_else(|e| {
+            respond(
+                502,
+                "application/json",
+                json!({ "message": e.to_string() }).to_string(),
+            )
+        });
+        println!(
+            "[Serve] {} {} -> {}",
+            method,
+            target,
+            response.status_code().0
+        );
+        response
+    } else {
+        match path {
+            "/" | "/index.html" => respond(200, "text/html; charset=utf-8", EXPLORER_HTML.into()),
+            "/openapi.json" => {
+                let spec = explorer.lock().unwrap().spec.to_string();
+                respond(200, "application/json", spec)
+            }
+            // polled by the page for live reload
+            "/__nid/state" => {
+                let explorer = explorer.lock().unwrap();
+                let state = json!({
+                    "version": explorer.version,
+                    "error": explorer.error,
+                    "proxy": backend.map(|_| PROXY_PREFIX),
+                });
+                respond(200, "application/json", state.to_string())
+            }
+            _ => respond(
+                404,
+                "application/json",
+                json!({ "message": format!("not found: {}", path) }).to_string(),
+            ),
+        }
+    };
+
+    if let Err(e) = request.respond(response) {
+        eprintln!("[Serve] respond failed: {}", e);
+    }
+}
+
+fn respond(
+    status: u16,
+    content_type: &str,
+    body: String,
+) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    tiny_http::Response::from_string(body)
+        .with_status_code(status)
+        .with_header(
+            tiny_http::Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()).unwrap(),
+        )
+}
+
+/// Forward a request to the backend as it is, and its response back.
+fn proxy(
+    request: &mut tiny_http::Request,
+    target: &str,
+) -> Result<tiny_http::Response<std::io::Cursor<Vec<u8>>>, anyhow::Error> {
+    let mut body = vec![];
+    request.as_reader().read_to_end(&mut body)?;
+
+    let client = reqwest::blocking::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let mut forward = client.request(request.method().as_str().parse()?, target);
+    for header in request.headers() {
+        let name = header.field.as_str().as_str();
+        // set by the client of the backend
+        if ["host", "content-length", "connection"]
+            .iter()
+            .any(|skip| name.eq_ignore_ascii_case(skip))
+        {
+            continue;
+        }
+        forward = forward.header(name, header.value.as_str());
+    }
+    let response = forward.body(body).send()?;
+
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter(|(name, _)| {
+            !["content-length", "transfer-encoding", "connection"].contains(&name.as_str())
+        })
+        .filter_map(|(name, value)| {
+            tiny_http::Header::from_bytes(name.as_str().as_bytes(), value.as_bytes()).ok()
+        })
+        .collect::<Vec<_>>();
+    let mut proxied =
+        tiny_http::Response::from_data(response.bytes()?.to_vec()).with_status_code(status);
+    for header in headers {
+        proxied.add_header(header);
+    }
+    Ok(proxied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve() {
+        let builder = OpenapiBuilder {
+            openapi: json!({
+                "servers": [
+                    { "url": "https://{region}.example.com/", "description": "Staging server", "variables": { "region": { "default": "eu" } } }
+                ]
+            }),
+        };
+        assert_eq!(
+            proxy_backend(&builder, "http://localhost:3000/").as_deref(),
+            Some("http://localhost:3000")
+        );
+        assert_eq!(
+            proxy_backend(&builder, "staging").as_deref(),
+            Some("https://eu.example.com")
+        );
+        assert_eq!(proxy_backend(&builder, "prod"), None);
+
+        assert_eq!(
+            proxy_url("http://localhost:3000", "/__nid/proxy/user/1?page=2").as_deref(),
+            Some("http://localhost:3000/user/1?page=2")
+        );
+        assert_eq!(proxy_url("http://localhost:3000", "/__nid/proxyx"), None);
+        assert_eq!(proxy_url("http://localhost:3000", "/openapi.json"), None);
+
+        let dir = std::env::temp_dir().join(format!("nid-serve-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("node_modules")).unwrap();
+        std::fs::write(dir.join("openapi.yaml"), "paths: {}\n").unwrap();
+        std::fs::write(dir.join("node_modules/pkg.json"), "{}").unwrap();
+        std::fs::write(dir.join("notes.md"), "").unwrap();
+        let (root, extensions) = watched(dir.join("openapi.yaml").to_str().unwrap()).unwrap();
+        assert_eq!(root, dir);
+        let before = fingerprint(&root, extensions);
+        assert_eq!(before.len(), 1);
+
+        std::fs::create_dir_all(dir.join("schemas")).unwrap();
+        std::fs::write(dir.join("schemas/user.json"), "{}").unwrap();
+        assert_ne!(fingerprint(&root, extensions), before);
+        assert!(watched("http://localhost:3000").is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
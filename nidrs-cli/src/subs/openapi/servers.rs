@@ -0,0 +1,287 @@
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    jsdoc,
+    naming::{ts_member, ts_property_key},
+    ts_string, OpenapiBuilder,
+};
+
+/// The project config holding the per environment server overrides, read from the working dir.
+pub const CONFIG_FILE: &str = "nidrs.config.toml";
+
+/// The `[openapi.servers]` table of the config, empty when the file does not exist.
+///
+/// eg: `staging = "https://staging.example.com"` or `production = { region = "eu" }`
+pub fn read_overrides(config: &Path) -> Result<toml::Table, anyhow::Error> {
+    let Ok(text) = std::fs::read_to_string(config) else {
+        return Ok(toml::Table::new());
+    };
+    let table = text
+        .parse::<toml::Table>()
+        .map_err(|e| anyhow::anyhow!("parse {}: {}", config.display(), e))?;
+    match table
+        .get("openapi")
+        .and_then(|openapi| openapi.get("servers"))
+    {
+        Some(toml::Value::Table(servers)) => Ok(servers.clone()),
+        Some(_) => Err(anyhow::anyhow!(
+            "{}: [openapi.servers] must be a table",
+            config.display()
+        )),
+        None => Ok(toml::Table::new()),
+    }
+}
+
+/// The url of a server with the defaults of its variables, without a trailing slash,
+/// eg: `https://{region}.example.com/` -> `https://eu.example.com`.
+pub fn server_url(server: &Value) -> String {
+    let mut url = server["url"].as_str().unwrap_or_default().to_string();
+    for (name, variable) in server["variables"].as_object().into_iter().flatten() {
+        if let Some(default) = variable["default"].as_str() {
+            url = url.replace(&format!("{{{}}}", name), default);
+        }
+    }
+    url.trim_end_matches('/').to_string()
+}
+
+impl OpenapiBuilder {
+    /// The servers of the spec keyed by their env, in the order of the spec.
+    ///
+    /// The env is `x-env`, or the first word of the description, eg: "Staging server" -> `staging`.
+    pub fn environments(&self) -> Vec<(String, &Value)> {
+        let mut environments: Vec<(String, &Value)> = vec![];
+        for (index, server) in self.openapi["servers"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let Some(url) = server["url"].as_str() else {
+                continue;
+            };
+            // the default server of openapi, requests already go there
+            if url == "/" && server.get("variables").is_none() {
+                continue;
+            }
+            let env = server["x-env"]
+                .as_str()
+                .or_else(|| {
+                    server["description"]
+                        .as_str()
+                        .and_then(|d| d.split_whitespace().next())
+                })
+                .map(|name| ts_member(&name.to_lowercase()))
+                .unwrap_or_else(|| format!("server{}", index));
+            let mut name = env.clone();
+            let mut n = 2;
+            while environments.iter().any(|(env, _)| *env == name) {
+                name = format!("{}{}", env, n);
+                n += 1;
+            }
+            environments.push((name, server));
+        }
+        environments
+    }
+
+    /// Apply the overrides of the config, an url replaces the server of an env or adds one,
+    /// a table sets the url and the defaults of the server variables.
+    pub fn override_servers(&mut self, overrides: &toml::Table) -> Result<(), anyhow::Error> {
+        if overrides.is_empty() {
+            return Ok(());
+        }
+        let mut servers = self
+            .environments()
+            .into_iter()
+            .map(|(env, server)| {
+                let mut server = server.clone();
+                server["x-env"] = json!(env);
+                server
+            })
+            .collect::<Vec<_>>();
+
+        for (env, value) in overrides {
+            let index = match servers.iter().position(|server| server["x-env"] == **env) {
+                Some(index) => index,
+                None => {
+                    servers.push(json!({ "x-env": env }));
+                    servers.len() - 1
+                }
+            };
+            let server = &mut servers[index];
+            match value {
+                toml::Value::String(url) => server["url"] = json!(url),
+                toml::Value::Table(table) => {
+                    for (key, value) in table {
+                        let Some(value) = value.as_str() else {
+                            return Err(anyhow::anyhow!(
+                                "[openapi.servers] {}.{} must be a string",
+                                env,
+                                key
+                            ));
+                        };
+                        if key == "url" {
+                            server["url"] = json!(value);
+                        } else {
+                            server["variables"][key]["default"] = json!(value);
+                        }
+                    }
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "[openapi.servers] {} must be an url or a table",
+                        env
+                    ))
+                }
+            }
+            if !server["url"].is_string() {
+                return Err(anyhow::anyhow!(
+                    "[openapi.servers] {} is not a server of the spec, give its url",
+                    env
+                ));
+            }
+        }
+        self.openapi["servers"] = Value::Array(servers);
+        Ok(())
+    }
+
+    /// The servers of the `Api`, `[{ env, url, variables }]` with the variable defaults.
+    pub fn to_servers(&self) -> Value {
+        let servers = self
+            .environments()
+            .into_iter()
+            .map(|(env, server)| {
+                let mut variables = Map::new();
+                for (name, variable) in server["variables"].as_object().into_iter().flatten() {
+                    if let Some(default) = variable["default"].as_str() {
+                        variables.insert(name.clone(), json!(default));
+                    }
+                }
+                let mut server = json!({ "env": env, "url": server["url"] });
+                if !variables.is_empty() {
+                    server["variables"] = Value::Object(variables);
+                }
+                server
+            })
+            .collect();
+        Value::Array(servers)
+    }
+
+    /// The `ApiEnv` type and the server members of `ApiOptions`.
+    pub fn ts_servers(&self) -> (String, String) {
+        let environments = self.environments();
+        let envs = environments
+            .iter()
+            .map(|(env, _)| ts_string(env))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let ts = format!("/* prettier-ignore */\nexport type ApiEnv = {};\n", envs);
+
+        let mut members = String::new();
+        let mut lines = environments
+            .iter()
+            .map(|(env, server)| {
+                let url = server["url"].as_str().unwrap_or_default();
+                match server["description"].as_str() {
+                    Some(description) => format!("- {}: {} ({})", env, url, description),
+                    None => format!("- {}: {}", env, url),
+                }
+            })
+            .collect::<Vec<_>>();
+        lines.insert(
+            0,
+            "the server requests go to, the first by default".to_string(),
+        );
+        members.push_str(&jsdoc(&lines, "  "));
+        members.push_str("  env?: ApiEnv;\n");
+        members.push_str(&jsdoc(
+            &["a base url used instead of `env`, eg: \"/api\"".to_string()],
+            "  ",
+        ));
+        members.push_str("  baseUrl?: string;\n");
+
+        let mut variables = std::collections::BTreeMap::new();
+        for (_, server) in &environments {
+            for (name, variable) in server["variables"].as_object().into_iter().flatten() {
+                variables.entry(name.as_str()).or_insert(variable);
+            }
+        }
+        if !variables.is_empty() {
+            members.push_str(&jsdoc(
+                &["values of the server variables".to_string()],
+                "  ",
+            ));
+            members.push_str("  variables?: {\n");
+            for (name, variable) in variables {
+                let mut lines = vec![];
+                if let Some(description) = variable["description"].as_str() {
+                    lines.extend(description.lines().map(|l| l.to_string()));
+                }
+                if let Some(default) = variable.get("default") {
+                    lines.push(format!("@default {}", default));
+                }
+                members.push_str(&jsdoc(&lines, "    "));
+                let t_type = match variable["enum"].as_array() {
+                    Some(values) if !values.is_empty() => values
+                        .iter()
+                        .filter_map(|value| value.as_str())
+                        .map(ts_string)
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                    _ => "string".to_string(),
+                };
+                members.push_str(&format!("    {}?: {};\n", ts_property_key(name), t_type));
+            }
+            members.push_str("  };\n");
+        }
+        (ts, members)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_servers() {
+        let mut builder = OpenapiBuilder {
+            openapi: json!({
+                "servers": [
+                    {
+                        "url": "https://{region}.example.com",
+                        "description": "Production server",
+                        "variables": { "region": { "default": "eu", "enum": ["eu", "us"] } }
+                    },
+                    { "url": "https://staging.example.com", "description": "Staging" }
+                ]
+            }),
+        };
+        let overrides = r#"
+            staging = "https://staging.internal"
+            local = "http://localhost:3000"
+            production = { region = "us" }
+        "#
+        .parse::<toml::Table>()
+        .unwrap();
+        builder.override_servers(&overrides).unwrap();
+
+        assert_eq!(
+            builder.to_servers(),
+            json!([
+                { "env": "production", "url": "https://{region}.example.com", "variables": { "region": "us" } },
+                { "env": "staging", "url": "https://staging.internal" },
+                { "env": "local", "url": "http://localhost:3000" }
+            ])
+        );
+        let (ts, members) = builder.ts_servers();
+        assert_eq!(
+            ts,
+            "/* prettier-ignore */\nexport type ApiEnv = 'production' | 'staging' | 'local';\n"
+        );
+        assert!(members.contains("    region?: 'eu' | 'us';\n"));
+
+        let unknown = "dev = { region = \"eu\" }".parse::<toml::Table>().unwrap();
+        assert!(builder.override_servers(&unknown).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use super::{OpenapiBuilder, HTTP_METHODS};
+
+/// Which api versions the generated client gets, when the spec has several.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Versions {
+    /// every version, as `api.v1.user` and `api.v2.user`
+    #[default]
+    All,
+    /// only the latest version of each controller, eg: `api.v2.user`
+    Latest,
+}
+
+/// The version of an operation, from `x-version` or a `/v2` segment of its path, eg: `v2`.
+pub fn version(path: &str, opr: &Value) -> Option<String> {
+    let version = match &opr["x-version"] {
+        Value::String(version) => version.clone(),
+        Value::Number(version) => version.to_string(),
+        _ => path
+            .split('/')
+            .find(|segment| {
+                segment.len() > 1
+                    && segment.starts_with(['v', 'V'])
+                    && segment[1..].chars().all(|c| c.is_ascii_digit())
+            })?
+            .to_string(),
+    };
+    let version = version.trim_start_matches(['v', 'V']).replace('.', "_");
+    (!version.is_empty()).then(|| format!("v{}", version))
+}
+
+/// The numbers of a version to order them, eg: `v1_10` -> `[1, 10]`.
+fn version_key(version: &str) -> Vec<u64> {
+    version[1..]
+        .split('_')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+impl OpenapiBuilder {
+    /// Namespace the controllers by the version of their operations when the spec has several versions,
+    /// eg: `UserController` of `/v2/user` -> `v2.UserController`. Unversioned operations stay as they are.
+    ///
+    /// With `Versions::Latest` the older versions of a controller are left out.
+    pub fn version_namespaces(&mut self, versions: Versions) {
+        let mut operations = vec![];
+        for (path, item) in self.openapi["paths"].as_object().into_iter().flatten() {
+            for (method, opr) in item.as_object().into_iter().flatten() {
+                let (true, Some(controller)) = (
+                    HTTP_METHODS.contains(&method.as_str()),
+                    opr["x-controller"].as_str(),
+                ) else {
+                    continue;
+                };
+                if let Some(version) = version(path, opr) {
+                    operations.push((
+                        path.clone(),
+                        method.clone(),
+                        controller.to_string(),
+                        version,
+                    ));
+                }
+            }
+        }
+
+        let mut latest = BTreeMap::<&str, &str>::new();
+        for (_, _, controller, version) in &operations {
+            let entry = latest.entry(controller).or_insert(version);
+            if version_key(version) > version_key(entry) {
+                *entry = version;
+            }
+        }
+        let mut distinct = operations
+            .iter()
+            .map(|(.., version)| version)
+            .collect::<Vec<_>>();
+        distinct.sort();
+        distinct.dedup();
+        if distinct.len() < 2 {
+            return;
+        }
+
+        let mut dropped = vec![];
+        for (path, method, controller, version) in &operations {
+            if versions == Versions::Latest && latest[controller.as_str()] != version {
+                dropped.push((path, method));
+                continue;
+            }
+            // the version goes right before the controller, eg: `billing.v2.InvoiceController`
+            let namespaced = match controller.rsplit_once('.') {
+                Some((namespaces, name)) => format!("{}.{}.{}", namespaces, version, name),
+                None => format!("{}.{}", version, controller),
+            };
+            self.openapi["paths"][path][method]["x-controller"] = json!(namespaced);
+        }
+
+        let Some(paths) = self.openapi["paths"].as_object_mut() else {
+            return;
+        };
+        for (path, method) in dropped {
+            if let Some(item) = paths.get_mut(path).and_then(|item| item.as_object_mut()) {
+                item.remove(method);
+                if !item.keys().any(|key| HTTP_METHODS.contains(&key.as_str())) {
+                    paths.remove(path);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_namespaces() {
+        let spec = json!({
+            "paths": {
+                "/api/v1/user/{id}": { "get": { "x-controller": "UserController", "x-router": "get_one" } },
+                "/api/v2/user/{id}": { "get": { "x-controller": "UserController", "x-router": "get_one" } },
+                "/api/v1/post": { "get": { "x-controller": "PostController", "x-router": "get_all" } },
+                "/api/health": { "get": { "x-controller": "AppController", "x-router": "health", "x-version": 10 } },
+                "/api/ping": { "get": { "x-controller": "AppController", "x-router": "ping" } }
+            }
+        });
+        assert_eq!(version("/v2/user", &json!({})), Some("v2".to_string()));
+        assert_eq!(version("/video/v", &json!({})), None);
+        assert_eq!(
+            version("/user", &json!({ "x-version": "1.1" })),
+            Some("v1_1".to_string())
+        );
+
+        let mut builder = OpenapiBuilder {
+            openapi: spec.clone(),
+        };
+        builder.version_namespaces(Versions::All);
+        let controllers = builder.controllers().into_keys().collect::<Vec<_>>();
+        assert_eq!(
+            controllers,
+            vec![
+                "AppController",
+                "v1.PostController",
+                "v1.UserController",
+                "v10.AppController",
+                "v2.UserController"
+            ]
+        );
+
+        let mut builder = OpenapiBuilder { openapi: spec };
+        builder.version_namespaces(Versions::Latest);
+        assert!(builder.openapi["paths"].get("/api/v1/user/{id}").is_none());
+        let controllers = builder.controllers().into_keys().collect::<Vec<_>>();
+        assert_eq!(
+            controllers,
+            vec![
+                "AppController",
+                "v1.PostController",
+                "v10.AppController",
+                "v2.UserController"
+            ]
+        );
+    }
+}
@@ -18,14 +18,19 @@ export function fetchAdapt(fetchFn: typeof fetch) {
       resHeaders[key] = value;
     });
 
+    // streams are read item by item by `streamHandler`
+    const streaming =
+      accept === "text/event-stream" || /ndjson|jsonl|stream\+json/.test(accept);
+
     const res = {
       status: response.status,
       statusText: response.statusText,
       headers: resHeaders,
-      data:
-        accept === "application/json"
-          ? await response.json()
-          : await response.text(),
+      data: streaming
+        ? response.body
+        : accept === "application/json"
+        ? await response.json()
+        : await response.text(),
     };
     if (env.DEBUG) console.log("[FetchAdapt] response:", res);
 
@@ -0,0 +1,84 @@
+import type { Routes } from "./index";
+
+/**
+ * A credential of the `Api` auth config, either the value or a function returning it,
+ * eg: `() => localStorage.getItem("token")`.
+ */
+export type Credential<T = string> =
+  | T
+  | (() => T | undefined | null | Promise<T | undefined | null>);
+
+/**
+ * A security scheme of the route table, eg: `{ type: "apiKey", in: "header", name: "X-API-Key" }`.
+ */
+export interface Scheme {
+  type: "http" | "apiKey" | "oauth2" | "openIdConnect" | string;
+  scheme?: string;
+  in?: "header" | "query" | "cookie";
+  name?: string;
+}
+
+/**
+ * Attach the credentials an operation requires to the request options built by `reqHandler`.
+ *
+ * The first alternative of the operation's `security` whose credentials are all configured wins,
+ * when none is the request is sent as is.
+ */
+export async function authHandler(
+  req: any,
+  method: string,
+  pathKey: string,
+  spec: Routes,
+  auth: { [scheme: string]: Credential<any> } = {}
+) {
+  const security = spec.paths?.[pathKey]?.[method]?.security ?? [];
+
+  for (const requirement of security) {
+    const credentials = await Promise.all(
+      requirement.map((name) => resolve(auth[name]))
+    );
+    if (credentials.every((credential) => credential != null && credential !== "")) {
+      requirement.forEach((name, i) =>
+        apply(req, spec.schemes?.[name], credentials[i])
+      );
+      return req;
+    }
+  }
+
+  return req;
+}
+
+async function resolve(credential: Credential<any> | undefined) {
+  return typeof credential === "function" ? await credential() : credential;
+}
+
+function apply(req: any, scheme: Scheme | undefined, credential: any) {
+  if (!scheme) return;
+  req.headers = req.headers ?? {};
+
+  if (scheme.type === "apiKey") {
+    const value = String(credential);
+    if (scheme.in === "query") {
+      req.url += `${req.url.includes("?") ? "&" : "?"}${scheme.name}=${encodeURIComponent(value)}`;
+    } else if (scheme.in === "cookie") {
+      const cookie = `${scheme.name}=${encodeURIComponent(value)}`;
+      req.headers.cookie = req.headers.cookie ? `${req.headers.cookie}; ${cookie}` : cookie;
+    } else {
+      req.headers[scheme.name] = value;
+    }
+    return;
+  }
+
+  if (scheme.type === "http" && scheme.scheme?.toLowerCase() === "basic") {
+    const { username, password } = credential;
+    req.headers.authorization = `Basic ${btoa(`${username}:${password}`)}`;
+    return;
+  }
+
+  // http bearer, oauth2 and openIdConnect send an access token
+  const prefix =
+    scheme.type === "http" && scheme.scheme && scheme.scheme.toLowerCase() !== "bearer"
+      ? scheme.scheme
+      : "Bearer";
+  req.headers.authorization = `${prefix} ${credential}`;
+}
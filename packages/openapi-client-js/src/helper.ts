@@ -24,3 +24,21 @@ export function P(key: string) {
 export function B(key: string) {
   return `body(${key})`;
 }
+
+/**
+ * Header params injection
+ * @param key
+ * @returns
+ */
+export function H(key: string) {
+  return `header(${key})`;
+}
+
+/**
+ * Cookie params injection
+ * @param key
+ * @returns
+ */
+export function C(key: string) {
+  return `cookie(${key})`;
+}
@@ -1,5 +1,5 @@
 import { HttpException } from "./errors";
-import { reqHandler, resHandler } from "./index";
+import { authHandler, reqHandler, resHandler, serverAdapt, serverUrl, streamHandler } from "./index";
 
 describe("Index", () => {
   it("should get reqHandler", () => {
@@ -152,6 +152,182 @@ describe("Index", () => {
     });
   });
 
+  it("should routes reqHandler", () => {
+    const dto = {
+      "path(id)": 12,
+      "query(id)": 22,
+      page: 1,
+      name: "test",
+    };
+    const routes = {
+      version: 2,
+      paths: {
+        "/test/{id}": {
+          post: {
+            params: { path: ["id"], query: ["id", "page"] },
+            body: ["name"],
+            contentType: "application/json",
+            accept: "application/json",
+          },
+        },
+      },
+    };
+
+    const result = reqHandler(dto, "post", "/test/{id}", routes);
+
+    expect(result).toEqual({
+      method: "POST",
+      url: "/test/12?id=22&page=1",
+      body: {
+        name: "test",
+      },
+      headers: {
+        accept: "application/json",
+        "content-type": "application/json",
+      },
+    });
+  });
+
+  it("should structured reqHandler", () => {
+    const dto = {
+      path: { id: 12 },
+      query: { id: 22, page: 1 },
+      header: { "x-trace": "abc" },
+      cookie: { session: "s 1" },
+      body: [1, 2],
+    };
+    const routes = {
+      version: 2,
+      paths: {
+        "/test/{id}": {
+          post: {
+            params: {
+              path: ["id"],
+              query: ["id", "page"],
+              header: ["x-trace"],
+              cookie: ["session"],
+            },
+            contentType: "application/json",
+          },
+        },
+      },
+    };
+
+    const result = reqHandler(dto, "post", "/test/{id}", routes, "structured");
+
+    expect(result).toEqual({
+      method: "POST",
+      url: "/test/12?id=22&page=1",
+      body: [1, 2],
+      headers: {
+        "x-trace": "abc",
+        cookie: "session=s%201",
+        accept: undefined,
+        "content-type": "application/json",
+      },
+    });
+  });
+
+  it("authHandler should attach the first configured security", async () => {
+    const routes = {
+      version: 2,
+      schemes: {
+        bearerAuth: { type: "http", scheme: "bearer" },
+        apiKey: { type: "apiKey", in: "query", name: "key" },
+        session: { type: "apiKey", in: "cookie", name: "sid" },
+      },
+      paths: {
+        "/test": {
+          get: { security: [["bearerAuth"], ["apiKey", "session"]] },
+          post: {},
+        },
+      },
+    };
+    const auth = { apiKey: "k 1", session: async () => "s1" };
+
+    const result = await authHandler(
+      reqHandler({ page: 1 }, "get", "/test", routes),
+      "get",
+      "/test",
+      routes,
+      auth
+    );
+    expect(result.url).toEqual("/test?page=1&key=k%201");
+    expect(result.headers.cookie).toEqual("sid=s1");
+
+    const secured = await authHandler(
+      reqHandler({}, "get", "/test", routes),
+      "get",
+      "/test",
+      routes,
+      { ...auth, bearerAuth: () => "t" }
+    );
+    expect(secured.headers.authorization).toEqual("Bearer t");
+    expect(secured.url).toEqual("/test");
+
+    const open = await authHandler(
+      reqHandler({}, "post", "/test", routes),
+      "post",
+      "/test",
+      routes,
+      { bearerAuth: "t" }
+    );
+    expect(open.headers.authorization).toBeUndefined();
+  });
+
+  it("serverAdapt should send to the selected server", async () => {
+    const servers = [
+      { env: "production", url: "https://{region}.example.com/", variables: { region: "eu" } },
+      { env: "staging", url: "https://staging.example.com" },
+    ];
+
+    expect(serverUrl(servers)).toEqual("https://eu.example.com");
+    expect(serverUrl(servers, { variables: { region: "us" } })).toEqual("https://us.example.com");
+    expect(serverUrl(servers, { env: "staging", baseUrl: "/api" })).toEqual("/api");
+    expect(() => serverUrl(servers, { env: "dev" })).toThrow("Unknown env dev");
+
+    const request = serverAdapt(async (req) => req.url, servers, { env: "staging" });
+    expect(await request(reqHandler({}, "get", "/test", { version: 2, paths: { "/test": { get: {} } } }))).toEqual(
+      "https://staging.example.com/test"
+    );
+  });
+
+  it("serverAdapt should keep the absolute paths of a merged spec", async () => {
+    // nid openapi merge users=./users.json billing=./billing.json --base-url billing=https://billing.example.com
+    const routes = {
+      version: 2,
+      paths: {
+        "/user/{id}": { get: { params: { path: ["id"] } } },
+        "https://billing.example.com/invoice/{id}": { get: { params: { path: ["id"] } } },
+      },
+    };
+    const request = serverAdapt(async (req) => req.url, [{ env: "production", url: "https://api.example.com" }]);
+
+    expect(await request(reqHandler({ id: 1 }, "get", "/user/{id}", routes))).toEqual("https://api.example.com/user/1");
+    expect(await request(reqHandler({ id: 1 }, "get", "https://billing.example.com/invoice/{id}", routes))).toEqual(
+      "https://billing.example.com/invoice/1"
+    );
+  });
+
+  it("should skip undefined query params", () => {
+    const routes = {
+      version: 2,
+      paths: { "/test": { get: { params: { query: ["page", "size"] } } } },
+    };
+
+    const result = reqHandler({ size: 0 }, "get", "/test", routes);
+
+    expect(result.url).toEqual("/test?size=0");
+  });
+
+  it("should reject newer routes", () => {
+    const routes = { version: 99, paths: {} };
+
+    expect(() => reqHandler({}, "get", "/test", routes)).toThrow(
+      "Routes version 99 is not supported"
+    );
+  });
+
   it("resHandler should return data", () => {
     const response = {
       status: 200,
@@ -173,4 +349,29 @@ describe("Index", () => {
       new HttpException("error", response)
     );
   });
+
+  it("streamHandler should iterate sse events", async () => {
+    const encoder = new TextEncoder();
+    const chunks = ['data: {"progress":', '1}\n\nevent: ping\ndata: done\n', "\n"];
+    const response = {
+      status: 200,
+      data: (async function* () {
+        for (const chunk of chunks) yield encoder.encode(chunk);
+      })(),
+    };
+
+    const items = [];
+    for await (const item of streamHandler(response, "sse")) items.push(item);
+
+    expect(items).toEqual([{ progress: 1 }, "done"]);
+  });
+
+  it("streamHandler should iterate ndjson lines", async () => {
+    const response = { status: 200, data: '{"a":1}\n\n{"a":2}' };
+
+    const items = [];
+    for await (const item of streamHandler(response, "ndjson")) items.push(item);
+
+    expect(items).toEqual([{ a: 1 }, { a: 2 }]);
+  });
 });
@@ -2,19 +2,123 @@ export * from "./adapt/fetchAdapt";
 export * from "./errors";
 export * from "./api";
 export * from "./helper";
+export * from "./stream";
+export * from "./auth";
+export * from "./server";
 
 import { ClientError, HttpException } from "./errors";
-import { B, P, Q } from "./helper";
+import { B, C, H, P, Q } from "./helper";
+import type { Scheme } from "./auth";
 
+/**
+ * The version of the route table generated by `nid openapi`, see `Api.routes`.
+ */
+export const ROUTES_VERSION = 2;
+
+/**
+ * What `reqHandler` needs to know about an operation.
+ */
+export interface Route {
+  params?: { [location: string]: string[] };
+  body?: string[];
+  contentType?: string;
+  accept?: string;
+  /** alternatives of security scheme names, any one fully configured is sent */
+  security?: string[][];
+}
+
+/**
+ * Compact route table emitted by `nid openapi`, eg:
+ * `{ version: 2, paths: { "/user/{id}": { get: { params: { path: ["id"] } } } } }`
+ */
+export interface Routes {
+  version: number;
+  paths: { [path: string]: { [method: string]: Route } };
+  schemes?: { [name: string]: Scheme };
+}
+
+/**
+ * Build the request options of an operation.
+ *
+ * `spec` is the route table of the generated `Api`, a whole openapi document is still accepted.
+ *
+ * `style` is how the dto is shaped, `flat` (`{ id, page, name }`) or
+ * `structured` (`{ path: { id }, query: { page }, body: { name } }`), see `nid openapi --params`.
+ */
 export function reqHandler(
   dto: any,
   method: string,
   pathKey: string,
-  { paths }: any
+  spec: Routes | any,
+  style: "flat" | "structured" = "flat"
 ) {
-  let url = "";
+  const route =
+    spec.version === undefined
+      ? openapiRoute(method, pathKey, spec)
+      : routesRoute(method, pathKey, spec);
+
+  const params = style === "structured" ? flattenDto(dto) : dto;
+
+  // Get the URL from the path
+  const url = transformUrlByDto(params, pathKey, route.params);
+
   let body = undefined;
+  if (style === "structured") {
+    // The body is passed as is, it may not be an object
+    body = dto?.body;
+  } else if (route.body) {
+    // Transform the DTO based on the request body keys
+    body = transformBodyByDto(dto, route.body);
+  }
+
+  return {
+    method: method.toUpperCase(),
+    url: url,
+    body: body,
+    headers: {
+      ...transformHeadersByDto(params, route.params),
+      ...transformCookiesByDto(params, route.params),
+      accept: route.accept,
+      "content-type": route.contentType,
+    },
+  };
+}
+
+export function resHandler<R = unknown>(response: any): R {
+  if (response.status >= 400) {
+    throw new HttpException(response.statusText, response);
+  }
+  return response.data as R;
+}
+
+function routesRoute(method: string, pathKey: string, routes: Routes): Route {
+  if (routes.version > ROUTES_VERSION) {
+    throw new ClientError(
+      `Routes version ${routes.version} is not supported, please upgrade @nidrs/openapi-client-js`,
+      routes
+    );
+  }
+
+  // Find the path in the routes object
+  const path = routes.paths?.[pathKey];
+
+  // Check if the path exists
+  if (!path) {
+    throw new ClientError(`Path not found: ${pathKey}`, routes.paths);
+  }
+
+  // Find the method in the path object
+  const route = path[method];
 
+  // Check if the method exists
+  if (!route) {
+    throw new ClientError(`Method not found: ${method}`, path);
+  }
+
+  return route;
+}
+
+function openapiRoute(method: string, pathKey: string, { paths }: any): Route {
   // Find the path in the openapi paths object
   const path = paths[pathKey];
 
@@ -29,8 +133,14 @@ export function reqHandler(
   if (!pathMethod) {
     throw new ClientError(`Method not found: ${method}`, path);
   }
-  // Get the URL from the path
-  url = transformUrlByDto(dto, pathKey, pathMethod?.parameters);
+
+  const params = {};
+  (pathMethod.parameters ?? []).forEach((param) => {
+    if (!params[param.in]) {
+      params[param.in] = [];
+    }
+    params[param.in].push(param.name);
+  });
 
   const contentType = extractContentType(pathMethod);
 
@@ -38,30 +148,16 @@ export function reqHandler(
   const requestBodySchema =
     pathMethod.requestBody?.content?.[contentType]?.schema;
 
-  // Check if the request body schema exists
-  if (requestBodySchema) {
-    // Validate and transform the DTO based on the request body schema
-    body = transformBodyByDto(dto, requestBodySchema);
-  }
-
   return {
-    method: method.toUpperCase(),
-    url: url,
-    body: body,
-    headers: {
-      accept: extractAccept(pathMethod),
-      "content-type": contentType,
-    },
+    params,
+    body: requestBodySchema
+      ? Object.keys(requestBodySchema.properties ?? {})
+      : undefined,
+    contentType,
+    accept: extractAccept(pathMethod),
   };
 }
 
-export function resHandler<R = unknown>(response: any): R {
-  if (response.status >= 400) {
-    throw new HttpException(response.statusText, response);
-  }
-  return response.data as R;
-}
-
 function extractAccept(pathMethod: any) {
   for (const responseCode in pathMethod.responses) {
     const response = pathMethod.responses[responseCode];
@@ -79,47 +175,78 @@ function extractContentType(pathMethod: any) {
   return undefined;
 }
 
-function transformBodyByDto(dto: any, schema: any) {
-  const body: any = {};
-
-  // Iterate over the properties of the schema
-  for (const key in schema.properties) {
-    const property = schema.properties[key];
-
-    // Check if the property is required and exists in the DTO
-    if (property.required && !dto[key]) {
-      throw new Error(`Missing required property: ${key}`);
+/**
+ * Key the params of a structured dto the way a flat dto names them when they collide, eg:
+ * `{ path: { id: 1 }, query: { id: 2 } }` -> `{ "path(id)": 1, "query(id)": 2 }`.
+ */
+function flattenDto(dto: any) {
+  const params: any = {};
+  const prefixes = { path: P, query: Q, header: H, cookie: C };
+  for (const location in prefixes) {
+    for (const name in dto?.[location] ?? {}) {
+      params[prefixes[location](name)] = dto[location][name];
     }
+  }
+  return params;
+}
+
+function transformBodyByDto(dto: any, keys: string[]) {
+  const body: any = {};
 
-    // Check if the property exists in the DTO
+  // Iterate over the keys of the body
+  for (const key of keys) {
     body[key] = dto[key] ?? dto[B(key)];
   }
 
   return body;
 }
 
-function transformUrlByDto(dto: any, url: string, parameters: any[] = []) {
-  const parametersMap = {};
+function transformHeadersByDto(dto: any, params: Route["params"] = {}) {
+  const headers = {};
+
+  params["header"]?.forEach((paramName) => {
+    const paramValue = dto[paramName] ?? dto[H(paramName)];
 
-  parameters.forEach((param) => {
-    if (!parametersMap[param.in]) {
-      parametersMap[param.in] = [];
+    if (paramValue !== undefined) {
+      headers[paramName] = String(paramValue);
     }
-    parametersMap[param.in].push(param);
   });
 
-  parametersMap["path"]?.forEach((param) => {
-    const paramName = param.name;
+  return headers;
+}
+
+function transformCookiesByDto(dto: any, params: Route["params"] = {}) {
+  const cookies: string[] = [];
+
+  params["cookie"]?.forEach((paramName) => {
+    const paramValue = dto[paramName] ?? dto[C(paramName)];
+
+    if (paramValue !== undefined) {
+      cookies.push(`${paramName}=${encodeURIComponent(paramValue)}`);
+    }
+  });
+
+  return cookies.length ? { cookie: cookies.join("; ") } : {};
+}
+
+function transformUrlByDto(
+  dto: any,
+  url: string,
+  params: Route["params"] = {}
+) {
+  params["path"]?.forEach((paramName) => {
     const paramValue = dto[paramName] ?? dto[P(paramName)];
 
     // Replace the parameter in the URL
     url = url.replace(`{${paramName}}`, encodeURIComponent(paramValue));
   });
 
-  parametersMap["query"]?.forEach((param) => {
-    const paramName = param.name;
+  params["query"]?.forEach((paramName) => {
     const paramValue = dto[paramName] ?? dto[Q(paramName)];
 
+    // Optional params left out are not sent
+    if (paramValue === undefined) return;
+
     // Append the parameter to the URL
     url += `${url.includes("?") ? "&" : "?"}${paramName}=${encodeURIComponent(
       paramValue
@@ -0,0 +1,56 @@
+import { ClientError } from "./errors";
+
+/**
+ * A server of the generated `Api`, eg: `{ env: "staging", url: "https://{region}.example.com", variables: { region: "eu" } }`.
+ */
+export interface Server {
+  env: string;
+  url: string;
+  variables?: { [name: string]: string };
+}
+
+/**
+ * Where the generated `Api` sends its requests, see `nid openapi` and `nidrs.config.toml`.
+ */
+export interface ServerOptions {
+  /** the env of a server, the first server by default */
+  env?: string;
+  /** a base url used instead of the servers */
+  baseUrl?: string;
+  /** values of the server variables, instead of their defaults */
+  variables?: { [name: string]: string };
+}
+
+/**
+ * The base url of the selected server, its variables filled in.
+ */
+export function serverUrl(servers: Server[], options: ServerOptions = {}) {
+  if (options.baseUrl != null) return options.baseUrl.replace(/\/$/, "");
+
+  const server = options.env
+    ? servers.find((server) => server.env === options.env)
+    : servers[0];
+  if (!server) {
+    throw new ClientError(`Unknown env ${options.env}`, { servers: servers.map((s) => s.env) });
+  }
+
+  const url = server.url.replace(
+    /\{([^}]+)\}/g,
+    (_, name) => options.variables?.[name] ?? server.variables?.[name] ?? ""
+  );
+  return url.replace(/\/$/, "");
+}
+
+/**
+ * Wrap a `request` adapter so the urls built by `reqHandler` go to the selected server.
+ *
+ * Absolute urls, eg: paths given a `--base-url https://…` by `nid openapi merge`, are sent as they are.
+ */
+export function serverAdapt(request: any, servers: Server[], options: ServerOptions = {}) {
+  const base = serverUrl(servers, options);
+  return (requestOptions: any) =>
+    request({
+      ...requestOptions,
+      url: /^[a-z][a-z\d+\-.]*:\/\//i.test(requestOptions.url) ? requestOptions.url : base + requestOptions.url,
+    });
+}
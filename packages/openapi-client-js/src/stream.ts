@@ -0,0 +1,77 @@
+import { HttpException } from "./errors";
+
+/**
+ * Iterate the items of a streaming response generated by `nid openapi`.
+ *
+ * `sse` yields the `data` of each `text/event-stream` event, `ndjson` yields each line,
+ * both parsed as json when they are json.
+ */
+export async function* streamHandler<T = unknown>(
+  response: any,
+  format: "sse" | "ndjson"
+): AsyncGenerator<T> {
+  if (response.status >= 400) {
+    throw new HttpException(response.statusText, response);
+  }
+
+  let data: string[] = [];
+  for await (const line of readLines(response.data)) {
+    if (format === "ndjson") {
+      if (line.trim()) yield parse(line) as T;
+      continue;
+    }
+
+    // a blank line dispatches the event
+    if (line === "") {
+      if (data.length) yield parse(data.join("\n")) as T;
+      data = [];
+    } else if (line.startsWith("data:")) {
+      data.push(line.slice(5).replace(/^ /, ""));
+    }
+  }
+  if (data.length) yield parse(data.join("\n")) as T;
+}
+
+function parse(text: string) {
+  try {
+    return JSON.parse(text);
+  } catch {
+    return text;
+  }
+}
+
+/**
+ * Split a body into lines, the body is a `ReadableStream`, an async iterable of chunks or a whole string.
+ */
+async function* readLines(body: any): AsyncGenerator<string> {
+  const decoder = new TextDecoder();
+  let buffer = "";
+  for await (const chunk of chunks(body)) {
+    buffer += typeof chunk === "string" ? chunk : decoder.decode(chunk, { stream: true });
+    const lines = buffer.split(/\r\n|\r|\n/);
+    buffer = lines.pop() ?? "";
+    yield* lines;
+  }
+  buffer += decoder.decode();
+  if (buffer) yield buffer;
+}
+
+async function* chunks(body: any): AsyncGenerator<string | Uint8Array> {
+  if (body == null) return;
+  if (typeof body === "string") {
+    yield body;
+  } else if (typeof body.getReader === "function") {
+    const reader = body.getReader();
+    try {
+      while (true) {
+        const { done, value } = await reader.read();
+        if (done) return;
+        yield value;
+      }
+    } finally {
+      reader.releaseLock();
+    }
+  } else {
+    yield* body;
+  }
+}